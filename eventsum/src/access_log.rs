@@ -0,0 +1,220 @@
+use crate::event::{Event, Level};
+use chrono::{DateTime, Utc};
+use regex::Regex;
+use serde_json::Value;
+use std::str::FromStr;
+
+/// nginx `combined` log_format
+pub const NGINX_COMBINED: &str =
+    r#"$remote_addr - $remote_user [$time_local] "$request" $status $body_bytes_sent "$http_referer" "$http_user_agent""#;
+
+/// Apache Common Log Format (%h %l %u %t "%r" %>s %b) in nginx variable notation
+pub const APACHE_COMMON: &str =
+    r#"$remote_addr $remote_logname $remote_user [$time_local] "$request" $status $body_bytes_sent"#;
+
+/// Variables that must appear in every log format
+const REQUIRED_VARIABLES: [&str; 2] = ["request", "status"];
+
+/// Variables holding the request time, every log format needs one of them
+const TIME_VARIABLES: [&str; 2] = ["time_local", "time_iso8601"];
+
+/// Variables mapped onto Event fields, all others become string extra fields
+const CONSUMED_VARIABLES: [&str; 5] = ["request", "remote_user", "time_local", "time_iso8601", "request_time"];
+
+/// Access log line format compiled from an nginx-style log_format string
+///
+/// Maps a request to an Event:
+/// - user: $remote_user, or $remote_addr if the user is "-"
+/// - action: method and path of $request, without the query string
+/// - duration_ms: $request_time (seconds), 0 if the format has none
+/// - level: HTTP $status, 5xx ERROR, 4xx WARN, everything else INFO
+/// - ts: $time_local or $time_iso8601, normalized to RFC 3339 UTC
+/// - extra: all other variables as strings, e.g. status and http_user_agent
+#[derive(Debug, Clone)]
+pub struct AccessLogFormat {
+    regex: Regex,
+}
+
+impl FromStr for AccessLogFormat {
+    type Err = String;
+
+    /// Compiles a log_format string such as `$remote_addr [$time_local] "$request" $status`
+    fn from_str(format: &str) -> Result<Self, Self::Err> {
+        let mut pattern = String::from("^");
+        let mut names: Vec<String> = Vec::new();
+        let mut rest = format;
+
+        while let Some(pos) = rest.find('$') {
+            pattern.push_str(&regex::escape(&rest[..pos]));
+            let after = &rest[pos + 1..];
+            let len = after
+                .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+                .unwrap_or(after.len());
+            if len == 0 {
+                return Err(format!("empty variable name in log format at '{}'", rest));
+            }
+
+            let name = &after[..len];
+            if names.iter().any(|n| n == name) {
+                // Repeated variables only need to match once
+                pattern.push_str(".*?");
+            } else {
+                pattern.push_str(&format!("(?P<{}>.*?)", name));
+                names.push(name.to_string());
+            }
+            rest = &after[len..];
+        }
+        pattern.push_str(&regex::escape(rest));
+        pattern.push('$');
+
+        for required in REQUIRED_VARIABLES {
+            if !names.iter().any(|n| n == required) {
+                return Err(format!("log format must contain ${}", required));
+            }
+        }
+        if !names.iter().any(|n| TIME_VARIABLES.contains(&n.as_str())) {
+            return Err("log format must contain $time_local or $time_iso8601".to_string());
+        }
+
+        let regex = Regex::new(&pattern).map_err(|e| format!("invalid log format: {}", e))?;
+        Ok(AccessLogFormat { regex })
+    }
+}
+
+/// Maps an HTTP status code to a level
+fn status_level(status: u16) -> Level {
+    match status {
+        500.. => Level::Error,
+        400..=499 => Level::Warn,
+        _ => Level::Info,
+    }
+}
+
+/// Converts an nginx $time_local (10/Oct/2000:13:55:36 -0700) or ISO 8601 time to RFC 3339 UTC
+fn normalize_time(time: &str) -> Result<String, String> {
+    DateTime::parse_from_str(time, "%d/%b/%Y:%H:%M:%S %z")
+        .or_else(|_| DateTime::parse_from_rfc3339(time))
+        .map(|ts| ts.with_timezone(&Utc).to_rfc3339_opts(chrono::SecondsFormat::AutoSi, true))
+        .map_err(|e| format!("invalid time '{}': {}", time, e))
+}
+
+impl AccessLogFormat {
+    /// Parses a single access log line into an Event
+    /// Returns a reason if the line does not match the format or a field is malformed
+    pub fn parse_line(&self, line: &str) -> Result<Event, String> {
+        let caps = self
+            .regex
+            .captures(line)
+            .ok_or_else(|| "line does not match the log format".to_string())?;
+        let field = |name: &str| caps.name(name).map(|m| m.as_str()).filter(|v| !v.is_empty() && *v != "-");
+
+        let request = field("request").ok_or("missing request")?;
+        let mut parts = request.split_whitespace();
+        let (Some(method), Some(target)) = (parts.next(), parts.next()) else {
+            return Err(format!("malformed request '{}'", request));
+        };
+        let path = target.split('?').next().unwrap_or(target);
+
+        let status_str = field("status").ok_or("missing status")?;
+        let status: u16 = status_str
+            .parse()
+            .map_err(|_| format!("invalid status '{}'", status_str))?;
+
+        let user = field("remote_user")
+            .or_else(|| field("remote_addr"))
+            .ok_or("missing remote_user and remote_addr")?;
+
+        let ts = match field("time_local").or_else(|| field("time_iso8601")) {
+            Some(time) => normalize_time(time)?,
+            None => String::new(),
+        };
+
+        let duration_ms = match field("request_time") {
+            Some(seconds) => {
+                let seconds: f64 = seconds
+                    .parse()
+                    .map_err(|_| format!("invalid request_time '{}'", seconds))?;
+                if !seconds.is_finite() || seconds < 0.0 {
+                    return Err(format!("invalid request_time '{}'", seconds));
+                }
+                (seconds * 1000.0).round() as u64
+            }
+            None => 0,
+        };
+
+        // Variables not used above, e.g. $status, $host or $http_user_agent
+        let extra = self
+            .regex
+            .capture_names()
+            .flatten()
+            .filter(|name| !CONSUMED_VARIABLES.contains(name))
+            .filter_map(|name| field(name).map(|value| (name.to_string(), Value::String(value.to_string()))))
+            .collect();
+
+        Ok(Event {
+            ts,
+            level: status_level(status),
+            user: user.to_string(),
+            action: format!("{} {}", method, path),
+            duration_ms,
+            extra,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_nginx_combined() {
+        let format: AccessLogFormat = NGINX_COMBINED.parse().unwrap();
+        let line = r#"10.0.0.1 - alice [19/Jan/2026:13:00:01 +0100] "GET /api/run?id=1 HTTP/1.1" 200 512 "-" "curl/8.0""#;
+
+        let event = format.parse_line(line).unwrap();
+
+        assert_eq!(event.user, "alice");
+        assert_eq!(event.action, "GET /api/run");
+        assert_eq!(event.level, Level::Info);
+        assert_eq!(event.ts, "2026-01-19T12:00:01Z");
+        assert_eq!(event.duration_ms, 0);
+    }
+
+    #[test]
+    fn test_apache_common_falls_back_to_ip() {
+        let format: AccessLogFormat = APACHE_COMMON.parse().unwrap();
+        let line = r#"10.0.0.1 - - [19/Jan/2026:12:00:01 +0000] "POST /upload HTTP/1.0" 503 -"#;
+
+        let event = format.parse_line(line).unwrap();
+
+        assert_eq!(event.user, "10.0.0.1");
+        assert_eq!(event.action, "POST /upload");
+        assert_eq!(event.level, Level::Error);
+    }
+
+    #[test]
+    fn test_custom_format_with_request_time() {
+        let format: AccessLogFormat = r#"$remote_addr [$time_iso8601] "$request" $status $request_time"#
+            .parse()
+            .unwrap();
+        let line = r#"10.0.0.1 [2026-01-19T12:00:01+00:00] "GET /login HTTP/2.0" 404 0.123"#;
+
+        let event = format.parse_line(line).unwrap();
+
+        assert_eq!(event.level, Level::Warn);
+        assert_eq!(event.duration_ms, 123);
+    }
+
+    #[test]
+    fn test_invalid_formats_and_lines() {
+        assert!("$remote_addr $status".parse::<AccessLogFormat>().is_err());
+        assert!("$ $request $status".parse::<AccessLogFormat>().is_err());
+        assert_eq!(
+            r#""$request" $status"#.parse::<AccessLogFormat>().unwrap_err(),
+            "log format must contain $time_local or $time_iso8601"
+        );
+
+        let format: AccessLogFormat = NGINX_COMBINED.parse().unwrap();
+        assert!(format.parse_line("not an access log line").is_err());
+    }
+}
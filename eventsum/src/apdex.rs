@@ -0,0 +1,154 @@
+use crate::event::Event;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Apdex counters and score for a group of events
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ApdexScore {
+    /// Events with duration <= T
+    pub satisfied: usize,
+    /// Events with T < duration <= 4T
+    pub tolerating: usize,
+    /// Events with duration > 4T, or at ERROR level or above
+    pub frustrated: usize,
+    /// (satisfied + tolerating / 2) / total, rounded to two decimals
+    pub score: f64,
+}
+
+impl ApdexScore {
+    /// Classifies a single event against the threshold
+    fn record(&mut self, event: &Event, threshold_ms: u64) {
+        let tolerating_limit = threshold_ms.saturating_mul(4);
+
+        if event.level.is_error() || event.duration_ms > tolerating_limit {
+            self.frustrated += 1;
+        } else if event.duration_ms > threshold_ms {
+            self.tolerating += 1;
+        } else {
+            self.satisfied += 1;
+        }
+    }
+
+    /// Computes the score from the counters. Returns 0 if nothing was recorded
+    fn finish(&mut self) {
+        let total = self.satisfied + self.tolerating + self.frustrated;
+        if total == 0 {
+            self.score = 0.0;
+            return;
+        }
+
+        let raw = (self.satisfied as f64 + self.tolerating as f64 / 2.0) / total as f64;
+        self.score = (raw * 100.0).round() / 100.0;
+    }
+}
+
+/// Apdex summary, overall and per action
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApdexSummary {
+    /// Target threshold T in milliseconds
+    pub threshold_ms: u64,
+    /// Score across all valid events
+    pub overall: ApdexScore,
+    /// Score per action, sorted by action name
+    pub by_action: BTreeMap<String, ApdexScore>,
+}
+
+impl ApdexSummary {
+    /// Computes the Apdex summary for the given events and threshold
+    pub fn from_events(events: &[Event], threshold_ms: u64) -> Self {
+        let mut overall = ApdexScore::default();
+        let mut by_action: BTreeMap<String, ApdexScore> = BTreeMap::new();
+
+        for event in events {
+            overall.record(event, threshold_ms);
+            by_action
+                .entry(event.action.clone())
+                .or_default()
+                .record(event, threshold_ms);
+        }
+
+        overall.finish();
+        for score in by_action.values_mut() {
+            score.finish();
+        }
+
+        ApdexSummary {
+            threshold_ms,
+            overall,
+            by_action,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::event::Level;
+
+    fn event(level: Level, action: &str, duration_ms: u64) -> Event {
+        Event { duration_ms, ..Event::test("alice", action, level) }
+    }
+
+    #[test]
+    fn test_apdex_classification() {
+        // T=100: 100 satisfied, 400 tolerating, 401 frustrated
+        let events = vec![
+            event(Level::Info, "a", 100),
+            event(Level::Info, "a", 400),
+            event(Level::Info, "a", 401),
+        ];
+
+        let summary = ApdexSummary::from_events(&events, 100);
+
+        assert_eq!(summary.overall.satisfied, 1);
+        assert_eq!(summary.overall.tolerating, 1);
+        assert_eq!(summary.overall.frustrated, 1);
+        // (1 + 0.5) / 3 = 0.5
+        assert_eq!(summary.overall.score, 0.5);
+    }
+
+    #[test]
+    fn test_apdex_error_always_frustrated() {
+        let events = vec![event(Level::Error, "a", 1)];
+
+        let summary = ApdexSummary::from_events(&events, 100);
+
+        assert_eq!(summary.overall.frustrated, 1);
+        assert_eq!(summary.overall.score, 0.0);
+    }
+
+    #[test]
+    fn test_apdex_per_action() {
+        let events = vec![
+            event(Level::Info, "login", 10),
+            event(Level::Info, "login", 20),
+            event(Level::Warn, "upload", 250),
+        ];
+
+        let summary = ApdexSummary::from_events(&events, 100);
+
+        assert_eq!(summary.by_action.len(), 2);
+        assert_eq!(summary.by_action["login"].score, 1.0);
+        assert_eq!(summary.by_action["upload"].tolerating, 1);
+        assert_eq!(summary.by_action["upload"].score, 0.5);
+        // (2 + 0.5) / 3 = 0.83
+        assert_eq!(summary.overall.score, 0.83);
+    }
+
+    #[test]
+    fn test_apdex_empty() {
+        let summary = ApdexSummary::from_events(&[], 100);
+
+        assert_eq!(summary.overall.score, 0.0);
+        assert!(summary.by_action.is_empty());
+    }
+
+    #[test]
+    fn test_apdex_huge_threshold_no_overflow() {
+        let events = vec![event(Level::Info, "a", u64::MAX)];
+
+        let summary = ApdexSummary::from_events(&events, u64::MAX / 2);
+
+        assert_eq!(summary.overall.tolerating, 1);
+    }
+}
@@ -1,41 +1,237 @@
+use crate::access_log::AccessLogFormat;
+use crate::binary::{BinaryFormat, LengthDelimited};
+use crate::cli::InputFormat;
 use crate::event::Event;
-use crate::result::SummaryResult;
+use crate::examples::ExampleCollector;
+use crate::field_stats::FieldStats;
+use crate::funnel::FunnelSummary;
+use crate::json_stream::JsonRecords;
+use crate::lines::{DEFAULT_MAX_LINE_BYTES, Line, Lines};
+use crate::mapping::FieldMapping;
+use crate::otlp::OtlpOptions;
+use crate::parser::{self, LineParser};
+use crate::pattern::LinePattern;
+use crate::quality::QualityTracker;
+use crate::result::{LevelCounts, SummaryResult};
+use crate::rules::RuleSet;
+use crate::sampling::{Decision, SampleMode, Sampler};
+use crate::schema::{EventSchema, SchemaViolations};
+use crate::sessions::SessionSummary;
+use crate::severity::LevelScale;
+use crate::stats;
+use chrono::{DateTime, Utc};
 use log::{debug, info, error,warn};
+use serde_json::Value;
 use std::collections::HashMap;
 use std::fs::File;
-use std::io::{self, BufRead, BufReader};
+use std::io::{self, BufRead, BufReader, Read};
 use std::path::Path;
+use std::time::Duration;
+
+/// Processing options derived from the command line
+#[derive(Debug, Clone)]
+pub struct Options {
+    /// Apdex threshold T in milliseconds. Apdex is skipped if None
+    pub apdex_threshold: Option<u64>,
+    /// Include per-action event counts and p95 in the summary
+    pub by_action: bool,
+    /// Track duplicates, out-of-order events and gaps
+    pub data_quality: bool,
+    /// Gap between consecutive events reported as too large
+    pub max_gap: Option<Duration>,
+    /// Exclude exact duplicate events from the aggregation
+    pub dedupe: bool,
+    /// Input format of the event stream
+    pub format: InputFormat,
+    /// Custom access log format, overrides the nginx/apache preset
+    pub log_format: Option<AccessLogFormat>,
+    /// Syslog structured-data param or message key holding duration_ms
+    pub syslog_duration_key: String,
+    /// Time the year of the first RFC 3164 timestamp is taken from, now if None
+    pub syslog_reference: Option<DateTime<Utc>>,
+    /// Custom line pattern, overrides the line format
+    pub pattern: Option<LinePattern>,
+    /// Field to JSON path mapping for JSON input
+    pub field_mapping: Option<FieldMapping>,
+    /// Attribute names for OTLP-JSON input
+    pub otlp: OtlpOptions,
+    /// Coerce loosely typed JSON fields instead of rejecting the line
+    pub lenient: bool,
+    /// Extra fields to report value counts and numeric stats for
+    pub field_stats: Vec<String>,
+    /// JSON Schema every event must satisfy
+    pub schema: Option<EventSchema>,
+    /// Validation rules checked on top of the required fields
+    pub rules: Option<RuleSet>,
+    /// Stop reading at the first bad line
+    pub strict: bool,
+    /// Largest accepted share of bad lines among all lines
+    pub max_bad_ratio: Option<f64>,
+    /// Largest accepted number of bad lines
+    pub max_bad_lines: Option<usize>,
+    /// Longer lines are bad lines and are not buffered
+    pub max_line_bytes: usize,
+    /// Count blank lines as bad lines instead of ignoring them
+    pub blank_lines_bad: bool,
+    /// Sample the valid events instead of aggregating all of them
+    pub sampling: Option<SampleMode>,
+    /// Seed of the sampler, also used to pick examples
+    pub sample_seed: u64,
+    /// Number of example events kept per level, user and action
+    pub examples: Option<usize>,
+    /// Custom levels accepted on top of the builtin ones
+    pub level_scale: LevelScale,
+}
+
+/// Line number and reason of a bad line
+#[derive(Debug, Clone, PartialEq)]
+pub struct BadLine {
+    pub line: usize,
+    pub reason: String,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            apdex_threshold: None,
+            by_action: false,
+            data_quality: false,
+            max_gap: None,
+            dedupe: false,
+            format: InputFormat::default(),
+            log_format: None,
+            syslog_duration_key: "duration_ms".to_string(),
+            syslog_reference: None,
+            pattern: None,
+            field_mapping: None,
+            otlp: OtlpOptions::default(),
+            lenient: false,
+            field_stats: Vec::new(),
+            schema: None,
+            rules: None,
+            strict: false,
+            max_bad_ratio: None,
+            max_bad_lines: None,
+            max_line_bytes: DEFAULT_MAX_LINE_BYTES,
+            blank_lines_bad: false,
+            sampling: None,
+            sample_seed: 0,
+            examples: None,
+            level_scale: LevelScale::default(),
+        }
+    }
+}
 
 pub struct App {
     // Vector of valid events
     events: Vec<Event>,
+    // Line each event in events was read from, only recorded with --examples
+    event_lines: Vec<usize>,
     // HashMap to track user counts
     user_counts: HashMap<String, usize>,
     // Placeholder: will hold Result struct later
     result: SummaryResult,
+    // Data quality tracker, only set when requested
+    quality: Option<QualityTracker>,
+    // Parser for line-based input formats
+    parser: Box<dyn LineParser>,
+    // Sampler of valid events, only set when sampling
+    sampler: Option<Sampler>,
+    // Example events per group, only set when requested
+    examples: Option<ExampleCollector>,
+    // First bad line, reported in strict mode
+    first_bad_line: Option<BadLine>,
+    // Processing options
+    options: Options,
 }
 
 impl App {
     /// Creates a new App instance
-    pub fn new() -> Self {
+    pub fn new(options: Options) -> Self {
+        let mut result = SummaryResult::new();
+        result.validation = options.rules.as_ref().map(RuleSet::empty_summary);
+
         App {
             events: Vec::new(),
+            event_lines: Vec::new(),
             user_counts: HashMap::new(),
-            result: SummaryResult::new(),
+            result,
+            quality: options
+                .data_quality
+                .then(|| QualityTracker::new(options.max_gap, options.dedupe)),
+            parser: parser::for_options(&options),
+            sampler: options.sampling.map(|mode| Sampler::new(mode, options.sample_seed)),
+            examples: options.examples.map(|size| ExampleCollector::new(size, options.sample_seed)),
+            first_bad_line: None,
+            options,
         }
     }
     
     /// Increments the count for a user
     fn increment_user_count(&mut self, user: &str) {
-        *self.user_counts.entry(user.to_string()).or_insert(0) += 1;
+        let count = self.user_counts.entry(user.to_string()).or_insert(0);
+        self.result.overflowed |= stats::add_count(count, 1);
     }
     
-    /// Finalizes the result by computing top users, p95, and outlier
+    /// Adds a valid event to the aggregation
+    fn add_event(&mut self, event: Event) {
+        self.increment_user_count(&event.user);
+        self.result.increment_events();
+        self.result.update_level_counts(&event.level);
+        self.events.push(event);
+    }
+
+    /// Recomputes the event, level and user counts from the events
+    /// Needed once a reservoir sample replaced events
+    fn recount_events(&mut self) {
+        let events = std::mem::take(&mut self.events);
+        self.user_counts.clear();
+        self.result.events = 0;
+        self.result.by_level = LevelCounts::default();
+        for event in events {
+            self.add_event(event);
+        }
+    }
+
+    /// Finalizes the result by computing top users, p95, outlier and optional sections
     pub fn finalize(&mut self) {
+        if self.options.schema.is_some() && self.result.schema_violations.is_none() {
+            self.result.schema_violations = Some(SchemaViolations::default());
+        }
+        if matches!(self.options.sampling, Some(SampleMode::Reservoir(_))) {
+            self.recount_events();
+        }
+        if let Some(ref sampler) = self.sampler {
+            self.result.sampling = Some(sampler.summary(&self.events));
+            self.result.overflowed |= sampler.overflowed();
+        }
         info!("Finalizing results: computing top users, p95, and outlier");
         self.result.compute_top_users(&self.user_counts);
+        // Examples are picked from the final sample, so evicted events cannot show up
+        if let Some(ref mut examples) = self.examples {
+            for (index, event) in self.events.iter().enumerate() {
+                examples.offer(index, event);
+            }
+            let top_users: Vec<String> = self.result.top_users.iter().map(|u| u.user.clone()).collect();
+            self.result.examples = Some(examples.examples(&self.events, &self.event_lines, &top_users));
+        }
         self.result.compute_p95_duration(&self.events);
         self.result.compute_outlier(&self.events);
+        if let Some(ref quality) = self.quality {
+            self.result.data_quality = Some(quality.summary());
+            self.result.overflowed |= quality.overflowed();
+        }
+        self.result.coercions = self.parser.coercions();
+        self.result.overflowed |= self.result.coercions.as_ref().is_some_and(|c| c.overflowed);
+        if self.options.by_action {
+            self.result.compute_by_action(&self.events);
+        }
+        if let Some(threshold) = self.options.apdex_threshold {
+            self.result.compute_apdex(&self.events, threshold);
+        }
+        if !self.options.field_stats.is_empty() {
+            self.result.field_stats = Some(FieldStats::from_events(&self.events, &self.options.field_stats));
+        }
     }
     
     /// Returns a reference to the result
@@ -43,42 +239,354 @@ impl App {
         &self.result
     }
     
+    /// Consumes the app and returns the result
+    pub fn into_result(self) -> SummaryResult {
+        self.result
+    }
+
+    /// Reconstructs per-user sessions from the valid events
+    pub fn session_summary(&self, gap: Duration) -> SessionSummary {
+        info!("Reconstructing sessions with gap {:?}", gap);
+        SessionSummary::from_events(&self.events, gap)
+    }
+
+    /// Computes the funnel over the given steps from the valid events
+    pub fn funnel_summary(
+        &self,
+        steps: &[String],
+        window: Option<Duration>,
+        with_transitions: bool,
+    ) -> FunnelSummary {
+        info!("Computing funnel over steps {:?}", steps);
+        FunnelSummary::from_events(&self.events, steps, window, with_transitions)
+    }
+
     /// Processes a single line (helper method)
     fn process_line(&mut self, line: &str) {
-        // Ignore blank lines
+        // Blank lines are ignored unless they count as bad
         if line.trim().is_empty() {
-            warn!("Skipping blank line but counting it");
-            self.result.increment_total_lines();
-            self.result.increment_bad_lines();
+            if self.options.blank_lines_bad {
+                warn!("Blank line {} counted as bad", self.line_number());
+                self.count_bad_line("blank line".to_string());
+            } else {
+                debug!("Skipping blank line {}", self.line_number());
+            }
+            self.result.increment_blank_lines();
             return;
         }
         
-        debug!("Processing line {}: {}", self.result.total_lines + 1, line);
+        debug!("Processing line {}: {}", self.line_number(), line);
         
-        // Parse event from JSON
-        match Event::from_json_line(line) {
-            Some(event) => {
+        // Parse events with the parser for the input format
+        let records = self.parser.parse_records(line, &self.options.level_scale);
+        let raw = self.schema_input(line);
+        self.process_records(records, raw.as_ref());
+    }
+
+    /// Returns the JSON value the schema validates for JSON and json-stream input, if a schema is set
+    /// Other formats, including OTLP exports, validate the parsed Event instead
+    fn schema_input(&self, text: &str) -> Option<Value> {
+        let json_input = matches!(self.options.format, InputFormat::Json | InputFormat::JsonStream);
+        if self.options.schema.is_none() || !json_input || self.options.pattern.is_some() {
+            return None;
+        }
+        serde_json::from_str(text).ok()
+    }
+
+    /// Processes every record parsed from one line or JSON value
+    /// A line without records (e.g. an empty OTLP export) still counts as one line
+    fn process_records(&mut self, records: Vec<Result<Event, String>>, raw: Option<&Value>) {
+        if records.is_empty() {
+            debug!("No records at line {}", self.line_number());
+            self.result.increment_total_lines();
+        }
+        for parsed in records {
+            self.process_event(parsed, raw);
+        }
+    }
+
+    /// Validates a parsed event and updates all counters (shared by all input formats)
+    /// raw is the JSON value the event was parsed from, validated by the schema if given
+    fn process_event(&mut self, parsed: Result<Event, String>, raw: Option<&Value>) {
+        if self.stopped() {
+            return;
+        }
+
+        match parsed {
+            Ok(event) => {
                 if event.is_valid() {
-                    self.events.push(event.clone());
-                    self.increment_user_count(&event.user);
-                    self.result.increment_events();
-                    self.result.update_level_counts(event.level);
+                    if !self.passes_rules(&event) {
+                        self.count_bad_line("validation rule failed".to_string());
+                        return;
+                    }
+                    if !self.passes_schema(&event, raw) {
+                        self.count_bad_line("schema violation".to_string());
+                        return;
+                    }
+                    let duplicate = self.quality.as_mut().is_some_and(|q| q.observe(&event));
+                    if duplicate && self.options.dedupe {
+                        debug!("Excluding duplicate event at line {}", self.line_number());
+                        self.result.increment_total_lines();
+                        return;
+                    }
+                    let line = self.line_number();
                     self.result.increment_total_lines();
+                    let decision = self.sampler.as_mut().map_or(Decision::Keep, |sampler| sampler.decide(&event));
+                    match decision {
+                        Decision::Keep => {
+                            if self.examples.is_some() {
+                                self.event_lines.push(line);
+                            }
+                            self.add_event(event);
+                        }
+                        Decision::Replace(slot) => {
+                            if self.examples.is_some() {
+                                self.event_lines[slot] = line;
+                            }
+                            self.events[slot] = event;
+                        }
+                        Decision::Skip => {}
+                    }
                 } else {
                     error!(
                         "Invalid event at line {}: missing required fields",
-                        self.result.total_lines + 1
+                        self.line_number()
                     );
-                    self.result.increment_bad_lines();
-                    self.result.increment_total_lines();
+                    self.count_bad_line("missing required fields".to_string());
+                }
+            }
+            Err(reason) => {
+                error!(
+                    "Failed to parse {} at line {}: {}",
+                    self.input_name(),
+                    self.line_number(),
+                    reason
+                );
+                self.count_bad_line(reason);
+            }
+        }
+    }
+
+    /// 1-based number of the line being processed, ignored blank lines included
+    fn line_number(&self) -> usize {
+        let skipped = if self.options.blank_lines_bad { 0 } else { self.result.blank_lines };
+        self.result.total_lines.saturating_add(skipped).saturating_add(1)
+    }
+
+    /// Counts a bad line, remembering the first one for strict mode
+    fn count_bad_line(&mut self, reason: String) {
+        if self.first_bad_line.is_none() {
+            self.first_bad_line = Some(BadLine {
+                line: self.line_number(),
+                reason,
+            });
+        }
+        self.result.increment_bad_lines();
+        self.result.increment_total_lines();
+    }
+
+    /// True once strict mode has seen a bad line, reading stops there
+    fn stopped(&self) -> bool {
+        self.options.strict && self.first_bad_line.is_some()
+    }
+
+    /// Returns the bad line that stopped reading in strict mode
+    pub fn strict_failure(&self) -> Option<&BadLine> {
+        self.first_bad_line.as_ref().filter(|_| self.options.strict)
+    }
+
+    /// Returns a message if the bad lines exceed --max-bad-ratio or --max-bad-lines
+    pub fn bad_line_limit_exceeded(&self) -> Option<String> {
+        let bad = self.result.bad_lines;
+        if let Some(max) = self.options.max_bad_lines
+            && bad > max
+        {
+            return Some(format!("{} bad lines exceed the limit of {}", bad, max));
+        }
+
+        let total = self.result.total_lines;
+        if let Some(max) = self.options.max_bad_ratio
+            && total > 0
+        {
+            let ratio = bad as f64 / total as f64;
+            if ratio > max {
+                return Some(format!("bad line ratio {:.4} ({} of {}) exceeds {}", ratio, bad, total, max));
+            }
+        }
+        None
+    }
+
+    /// Checks the event against the validation rules, if any, logging and counting each violation
+    fn passes_rules(&mut self, event: &Event) -> bool {
+        let Some(ref rules) = self.options.rules else {
+            return true;
+        };
+        let violations = rules.violations(event);
+        if violations.is_empty() {
+            return true;
+        }
+
+        for (name, reason) in &violations {
+            error!(
+                "Rule {} failed at line {}: {}",
+                name,
+                self.line_number(),
+                reason
+            );
+        }
+        if let Some(ref mut validation) = self.result.validation {
+            self.result.overflowed |= validation.record(&violations);
+        }
+        false
+    }
+
+    /// Checks the raw JSON value, or the event without one, against the schema, if any
+    /// Logs and counts each violation
+    fn passes_schema(&mut self, event: &Event, raw: Option<&Value>) -> bool {
+        let Some(ref schema) = self.options.schema else {
+            return true;
+        };
+        let violations = match raw {
+            Some(value) => schema.value_violations(value),
+            None => schema.violations(event),
+        };
+        if violations.is_empty() {
+            return true;
+        }
+
+        for violation in &violations {
+            error!(
+                "Schema violation at line {}: {}",
+                self.line_number(),
+                violation.reason
+            );
+        }
+        self.result.overflowed |= self
+            .result
+            .schema_violations
+            .get_or_insert_with(SchemaViolations::default)
+            .record(&violations);
+        false
+    }
+
+    /// Name of the input format used in log messages
+    fn input_name(&self) -> &'static str {
+        if self.options.pattern.is_some() {
+            "pattern"
+        } else {
+            self.options.format.name()
+        }
+    }
+
+    /// Reads CSV/TSV records with a header row, mapping columns by header name
+    /// Quoted fields may contain delimiters and newlines
+    fn read_delimited<R: Read>(&mut self, reader: R, delimiter: u8) -> io::Result<()> {
+        let mut csv_reader = csv::ReaderBuilder::new()
+            .delimiter(delimiter)
+            .flexible(true)
+            .trim(csv::Trim::Headers)
+            .from_reader(reader);
+
+        let headers = match csv_reader.headers() {
+            Ok(headers) => headers.clone(),
+            Err(e) => return Err(csv_error_to_io(e)),
+        };
+        debug!("CSV header: {:?}", headers);
+
+        for record in csv_reader.records() {
+            if self.stopped() {
+                break;
+            }
+            match record {
+                Ok(record) => {
+                    debug!("Processing record {}: {:?}", self.line_number(), record);
+                    self.process_event(Event::from_csv_record(&record, &headers, &self.options.level_scale), None);
+                }
+                // I/O errors abort the run, malformed records are bad lines
+                Err(e) if matches!(e.kind(), csv::ErrorKind::Io(_)) => {
+                    return Err(csv_error_to_io(e));
                 }
+                Err(e) => self.process_event(Err(format!("malformed record: {}", e)), None),
             }
-            None => {
-                error!("Failed to parse JSON at line {}", self.result.total_lines + 1);
-                self.result.increment_bad_lines();
-                self.result.increment_total_lines();
+        }
+
+        Ok(())
+    }
+
+    /// Reads a stream of JSON values (concatenated, pretty-printed or in arrays)
+    /// Bad records report their line and byte offset
+    fn read_json_stream<R: BufRead>(&mut self, reader: R) -> io::Result<()> {
+        for record in JsonRecords::new(reader, Some(self.options.max_line_bytes)) {
+            if self.stopped() {
+                break;
+            }
+            let record = record?;
+            let mut raw = None;
+            let records = match std::str::from_utf8(&record.bytes) {
+                _ if record.too_long => vec![Err(format!(
+                    "record exceeds --max-line-bytes ({} bytes)",
+                    self.options.max_line_bytes
+                ))],
+                Ok(text) => {
+                    debug!("Processing record {} at line {}: {}", self.line_number(), record.line, text);
+                    raw = self.schema_input(text);
+                    self.parser.parse_records(text, &self.options.level_scale)
+                }
+                Err(e) => vec![Err(format!("invalid UTF-8: {}", e))],
+            };
+            let records = records
+                .into_iter()
+                .map(|parsed| {
+                    parsed.map_err(|reason| {
+                        format!("{} (record at line {}, byte {})", reason, record.line, record.offset)
+                    })
+                })
+                .collect();
+            self.process_records(records, raw.as_ref());
+        }
+        Ok(())
+    }
+
+    /// Reads length-delimited MessagePack or CBOR records
+    fn read_binary<R: Read>(&mut self, reader: R, format: BinaryFormat) -> io::Result<()> {
+        for record in LengthDelimited::new(reader, self.options.max_line_bytes) {
+            if self.stopped() {
+                break;
+            }
+            let parsed = record?.and_then(|bytes| format.decode_event(&bytes, &self.options.level_scale));
+            self.process_event(parsed, None);
+        }
+        Ok(())
+    }
+
+    /// Reads events from any buffered reader according to the input format
+    fn read_from_reader<R: BufRead>(&mut self, reader: R) -> io::Result<()> {
+        // A custom pattern always reads lines
+        match self.options.format {
+            _ if self.options.pattern.is_some() => {}
+            InputFormat::Csv => return self.read_delimited(reader, b','),
+            InputFormat::Tsv => return self.read_delimited(reader, b'\t'),
+            // OTLP exports are one request per line or pretty-printed across lines
+            InputFormat::JsonStream | InputFormat::OtlpJson => return self.read_json_stream(reader),
+            InputFormat::Msgpack => return self.read_binary(reader, BinaryFormat::MessagePack),
+            InputFormat::Cbor => return self.read_binary(reader, BinaryFormat::Cbor),
+            _ => {}
+        }
+
+        for line in Lines::new(reader, Some(self.options.max_line_bytes)) {
+            if self.stopped() {
+                break;
+            }
+            match line? {
+                Line::Text(text) => self.process_line(&text),
+                bad => {
+                    let reason = bad.bad_reason().unwrap_or_default();
+                    error!("Bad line {}: {}", self.line_number(), reason);
+                    self.count_bad_line(reason);
+                }
             }
         }
+        Ok(())
     }
 
     /// Reads events from a file at the given path
@@ -86,12 +594,7 @@ impl App {
     pub fn read_from_file(&mut self, path: &str) -> io::Result<()> {
         info!("Opening file: {}", path);
         let file = File::open(Path::new(path))?;
-        let reader = BufReader::new(file);
-
-        for line in reader.lines() {
-            let line = line?;
-            self.process_line(&line);
-        }
+        self.read_from_reader(BufReader::new(file))?;
 
         info!("Finished reading {} lines from file", self.result.total_lines);
         Ok(())
@@ -102,14 +605,17 @@ impl App {
     pub fn read_from_stdin(&mut self) -> io::Result<()> {
         info!("Reading from stdin");
         let stdin = io::stdin();
-        let reader = stdin.lock();
-
-        for line in reader.lines() {
-            let line = line?;
-            self.process_line(&line);
-        }
+        self.read_from_reader(stdin.lock())?;
 
         info!("Finished reading {} lines from stdin", self.result.total_lines);
         Ok(())
     }
 }
+
+/// Converts a CSV reader error into an I/O error
+fn csv_error_to_io(e: csv::Error) -> io::Error {
+    match e.into_kind() {
+        csv::ErrorKind::Io(e) => e,
+        kind => io::Error::new(io::ErrorKind::InvalidData, format!("{:?}", kind)),
+    }
+}
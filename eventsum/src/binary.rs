@@ -0,0 +1,197 @@
+use crate::event::{Event, RawEvent};
+use crate::severity::LevelScale;
+use serde::Serialize;
+use std::io::{self, Read};
+
+/// Binary serialization formats for records and summaries
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BinaryFormat {
+    MessagePack,
+    Cbor,
+}
+
+impl BinaryFormat {
+    /// Decodes a single record into an Event, resolving its level against the scale
+    /// Returns the reason if the record cannot be decoded
+    pub fn decode_event(&self, bytes: &[u8], scale: &LevelScale) -> Result<Event, String> {
+        let raw: RawEvent = match self {
+            BinaryFormat::MessagePack => {
+                rmp_serde::from_slice(bytes).map_err(|e| format!("invalid MessagePack: {}", e))?
+            }
+            BinaryFormat::Cbor => ciborium::from_reader(bytes).map_err(|e| format!("invalid CBOR: {}", e))?,
+        };
+        raw.resolve(scale)
+    }
+
+    /// Encodes a value. MessagePack uses maps with field names so optional fields can be skipped
+    pub fn encode<T: Serialize>(&self, value: &T) -> Result<Vec<u8>, String> {
+        match self {
+            BinaryFormat::MessagePack => rmp_serde::to_vec_named(value).map_err(|e| e.to_string()),
+            BinaryFormat::Cbor => {
+                let mut bytes = Vec::new();
+                ciborium::into_writer(value, &mut bytes).map_err(|e| e.to_string())?;
+                Ok(bytes)
+            }
+        }
+    }
+}
+
+/// Reads records framed by a 4-byte big-endian length prefix
+///
+/// Yields Ok(Err(reason)) for a truncated final record and for a record longer than
+/// max_bytes (--max-line-bytes) so they are counted as bad records. An oversized record
+/// is skipped without buffering it and reading continues with the next one.
+pub struct LengthDelimited<R: Read> {
+    reader: R,
+    max_bytes: usize,
+    done: bool,
+}
+
+impl<R: Read> LengthDelimited<R> {
+    pub fn new(reader: R, max_bytes: usize) -> Self {
+        LengthDelimited {
+            reader,
+            max_bytes,
+            done: false,
+        }
+    }
+
+    /// Fills buf as far as possible, returns the number of bytes read
+    fn read_up_to(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let mut filled = 0;
+        while filled < buf.len() {
+            match self.reader.read(&mut buf[filled..]) {
+                Ok(0) => break,
+                Ok(n) => filled += n,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(filled)
+    }
+}
+
+impl<R: Read> Iterator for LengthDelimited<R> {
+    type Item = io::Result<Result<Vec<u8>, String>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+
+        let mut prefix = [0u8; 4];
+        match self.read_up_to(&mut prefix) {
+            Ok(0) => {
+                self.done = true;
+                return None;
+            }
+            Ok(4) => {}
+            Ok(n) => {
+                self.done = true;
+                return Some(Ok(Err(format!("truncated length prefix ({} of 4 bytes)", n))));
+            }
+            Err(e) => return Some(Err(e)),
+        }
+
+        let len = u32::from_be_bytes(prefix) as usize;
+        if len > self.max_bytes {
+            let skipped = match io::copy(&mut (&mut self.reader).take(len as u64), &mut io::sink()) {
+                Ok(skipped) => skipped,
+                Err(e) => return Some(Err(e)),
+            };
+            if skipped < len as u64 {
+                self.done = true;
+                return Some(Ok(Err(format!("truncated record ({} of {} bytes)", skipped, len))));
+            }
+            return Some(Ok(Err(format!("record exceeds --max-line-bytes ({} bytes)", self.max_bytes))));
+        }
+
+        let mut bytes = vec![0u8; len];
+        match self.read_up_to(&mut bytes) {
+            Ok(n) if n == len => Some(Ok(Ok(bytes))),
+            Ok(n) => {
+                self.done = true;
+                Some(Ok(Err(format!("truncated record ({} of {} bytes)", n, len))))
+            }
+            Err(e) => Some(Err(e)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::event::Level;
+
+    fn event() -> Event {
+        Event { duration_ms: 120, ..Event::test("alice", "login", Level::Warn) }
+    }
+
+    fn frame(records: &[Vec<u8>]) -> Vec<u8> {
+        let mut stream = Vec::new();
+        for record in records {
+            stream.extend_from_slice(&(record.len() as u32).to_be_bytes());
+            stream.extend_from_slice(record);
+        }
+        stream
+    }
+
+    #[test]
+    fn test_round_trip() {
+        for format in [BinaryFormat::MessagePack, BinaryFormat::Cbor] {
+            let bytes = format.encode(&event()).unwrap();
+            assert_eq!(format.decode_event(&bytes, &LevelScale::default()).unwrap(), event());
+            assert!(format.decode_event(b"\xc1garbage", &LevelScale::default()).is_err());
+        }
+    }
+
+    #[test]
+    fn test_msgpack_array_encoding() {
+        // Producers may encode events as arrays [ts, level, user, action, duration_ms]
+        let record = ("2026-01-19T12:00:01Z", "WARN", "alice", "login", 120u64);
+        let bytes = rmp_serde::to_vec(&record).unwrap();
+        assert_eq!(bytes[0], 0x95, "expected a 5-element MessagePack array");
+        assert_eq!(BinaryFormat::MessagePack.decode_event(&bytes, &LevelScale::default()).unwrap(), event());
+
+        let short = rmp_serde::to_vec(&("2026-01-19T12:00:01Z", "WARN", "alice", "login")).unwrap();
+        assert!(BinaryFormat::MessagePack.decode_event(&short, &LevelScale::default()).is_err());
+    }
+
+    #[test]
+    fn test_length_delimited() {
+        let stream = frame(&[b"abc".to_vec(), Vec::new(), b"xy".to_vec()]);
+
+        let records: Vec<_> = LengthDelimited::new(&stream[..], 1024).map(|r| r.unwrap().unwrap()).collect();
+
+        assert_eq!(records, vec![b"abc".to_vec(), Vec::new(), b"xy".to_vec()]);
+    }
+
+    #[test]
+    fn test_length_delimited_truncated() {
+        let mut stream = frame(&[b"abc".to_vec()]);
+        stream.extend_from_slice(&[0, 0, 0, 10, 1, 2]);
+
+        let records: Vec<_> = LengthDelimited::new(&stream[..], 1024).map(|r| r.unwrap()).collect();
+
+        assert_eq!(records.len(), 2);
+        assert!(records[0].is_ok());
+        assert!(records[1].as_ref().unwrap_err().contains("truncated record"));
+    }
+
+    #[test]
+    fn test_length_delimited_oversized() {
+        let mut stream = frame(&[b"ok".to_vec(), b"too long".to_vec(), b"ok".to_vec()]);
+        stream.extend_from_slice(&[0xff, 0xff, 0xff, 0xff, 1, 2, 3]);
+
+        let mut records = LengthDelimited::new(&stream[..], 4);
+
+        assert_eq!(records.next().unwrap().unwrap(), Ok(b"ok".to_vec()));
+        assert_eq!(
+            records.next().unwrap().unwrap().unwrap_err(),
+            "record exceeds --max-line-bytes (4 bytes)"
+        );
+        assert_eq!(records.next().unwrap().unwrap(), Ok(b"ok".to_vec()));
+        assert!(records.next().unwrap().unwrap().unwrap_err().contains("truncated record"));
+        assert!(records.next().is_none());
+    }
+}
@@ -1,4 +1,17 @@
-use clap::Parser;
+use crate::access_log::AccessLogFormat;
+use crate::app::Options;
+use crate::lines::DEFAULT_MAX_LINE_BYTES;
+use crate::mapping::{FieldMapping, FieldPath};
+use crate::otlp::OtlpOptions;
+use crate::pattern::LinePattern;
+use crate::rules::RuleSet;
+use crate::sampling::SampleMode;
+use crate::schema::EventSchema;
+use crate::severity::LevelScale;
+use crate::diff::Thresholds;
+use chrono::{DateTime, Utc};
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use std::time::Duration;
 
 #[derive(Parser)]
 #[command(name = "eventsum")]
@@ -6,12 +19,376 @@ use clap::Parser;
 #[command(long_about = "Parses event log (JSON Lines) and produces a summary report.\n\nLogging:\n  Set RUST_LOG environment variable to control log output:\n  - RUST_LOG=error  : Errors only\n  - RUST_LOG=info   : Major operations\n  - RUST_LOG=debug  : Detailed line processing\n  - RUST_LOG=trace  : Maximum verbosity\n\nExample:\n  RUST_LOG=info eventsum --input events.jsonl")]
 pub struct Cli {
     /// Input file path. If omitted, reads from stdin
-    #[arg(short, long)]
+    #[arg(short, long, global = true)]
     pub input: Option<String>,
 
     /// Pretty-print the output JSON
-    #[arg(long)]
+    #[arg(long, global = true)]
     pub pretty: bool,
+
+    /// Input format. csv and tsv require a header row naming the event fields
+    #[arg(long, global = true, value_enum, default_value_t = InputFormat::Json)]
+    pub format: InputFormat,
+
+    /// Output format of the report
+    #[arg(long, global = true, value_enum, default_value_t = OutputFormat::Json)]
+    pub output_format: OutputFormat,
+
+    /// Custom nginx-style log_format for access logs, e.g. '[$time_local] "$request" $status $request_time'
+    #[arg(long, global = true, value_parser = clap::value_parser!(AccessLogFormat))]
+    pub log_format: Option<AccessLogFormat>,
+
+    /// OTLP attribute holding the user (falls back to service.name)
+    #[arg(long, global = true, default_value = "enduser.id", value_name = "KEY")]
+    pub otlp_user_attr: String,
+
+    /// OTLP attribute holding the action (falls back to the span name or log body)
+    #[arg(long, global = true, default_value = "event.name", value_name = "KEY")]
+    pub otlp_action_attr: String,
+
+    /// OTLP log record attribute holding duration_ms (spans use end - start)
+    #[arg(long, global = true, default_value = "duration_ms", value_name = "KEY")]
+    pub otlp_duration_attr: String,
+
+    /// Custom line pattern: regex with named groups ts, level, user, action and optional duration_ms.
+    /// Grok references like %{TIMESTAMP_ISO8601:ts} are expanded. Overrides --format
+    #[arg(long, global = true, value_parser = clap::value_parser!(LinePattern))]
+    pub pattern: Option<LinePattern>,
+
+    /// Map an event field to a JSON path for JSON input, e.g. --map user=ctx.user.id (repeatable)
+    #[arg(long = "map", global = true, value_name = "FIELD=PATH", value_parser = clap::value_parser!(FieldPath))]
+    pub map: Vec<FieldPath>,
+
+    /// TOML file with `field = "json.path"` mappings. --map entries take precedence
+    #[arg(long, global = true, value_name = "PATH")]
+    pub map_file: Option<String>,
+
+    /// Stop at the first bad line, report it and exit with code 4 without a summary
+    #[arg(long, global = true)]
+    pub strict: bool,
+
+    /// Exit with code 5 after printing the summary if more than this share of lines is bad (0-1)
+    #[arg(long, global = true, value_name = "RATIO", value_parser = parse_ratio)]
+    pub max_bad_ratio: Option<f64>,
+
+    /// Exit with code 5 after printing the summary if there are more bad lines than this
+    #[arg(long, global = true, value_name = "N")]
+    pub max_bad_lines: Option<usize>,
+
+    /// Sample this share of valid events (Bernoulli), or of users with --sample-by user
+    #[arg(long, global = true, value_name = "RATE", value_parser = parse_ratio, conflicts_with = "sample_n")]
+    pub sample: Option<f64>,
+
+    /// Keep a uniform sample of at most N valid events (reservoir)
+    #[arg(long, global = true, value_name = "N")]
+    pub sample_n: Option<usize>,
+
+    /// Sample whole users by a stable hash of the name, so per-user stats stay consistent
+    #[arg(long, global = true, value_enum, requires = "sample")]
+    pub sample_by: Option<SampleKey>,
+
+    /// Seed of the sampler. Runs with the same seed sample the same events
+    #[arg(long, global = true, default_value_t = 0, value_name = "SEED")]
+    pub sample_seed: u64,
+
+    /// Keep K example events per level, top user and top action, with their line numbers
+    #[arg(long, global = true, value_name = "K")]
+    pub examples: Option<usize>,
+
+    /// Count blank and whitespace-only lines as bad lines instead of ignoring them
+    #[arg(long, global = true)]
+    pub blank_lines_bad: bool,
+
+    /// Longer lines (without line ending), json-stream records and binary records are counted as
+    /// bad lines without being buffered
+    #[arg(long, global = true, value_name = "BYTES", default_value_t = DEFAULT_MAX_LINE_BYTES)]
+    pub max_line_bytes: usize,
+
+    /// TOML file with validation rules (user_pattern, allowed_actions, max_duration,
+    /// no_future_ts, max_ts_age, max_field_length). Rejected events are bad lines
+    #[arg(long, global = true, value_name = "PATH")]
+    pub rules: Option<String>,
+
+    /// Turns off a rule of the rules file, can be repeated
+    #[arg(long, global = true, value_name = "RULE", requires = "rules")]
+    pub disable_rule: Vec<String>,
+
+    /// JSON Schema file each event (with its extra fields) must satisfy
+    /// JSON input is validated as read, before --map and --lenient, other formats as parsed events.
+    /// Violating events are bad lines, violations are counted per schema rule
+    #[arg(long, global = true, value_name = "PATH")]
+    pub schema: Option<String>,
+
+    /// Extra fields to report value counts, distinct counts and numeric stats for
+    #[arg(long, global = true, value_delimiter = ',', value_name = "FIELD")]
+    pub field_stats: Vec<String>,
+
+    /// TOML file defining custom levels as `NAME = severity`, on the OpenTelemetry scale
+    /// (TRACE 1, DEBUG 5, INFO 9, WARN 13, ERROR 17, FATAL 21, max 24)
+    #[arg(long, global = true, value_name = "PATH")]
+    pub level_scale: Option<String>,
+
+    /// Coerce loosely typed JSON fields (numeric strings, fractional or negative durations,
+    /// null durations, level case and aliases). Coercions are counted in the summary
+    #[arg(long, global = true)]
+    pub lenient: bool,
+
+    /// Structured-data param or message key holding duration_ms in syslog input
+    #[arg(long, global = true, default_value = "duration_ms", value_name = "KEY")]
+    pub syslog_duration_key: String,
+
+    /// Time RFC 3164 syslog timestamps (which have no year) are read relative to, e.g.
+    /// 2026-01-19T00:00:00Z. Defaults to now; later lines follow the previous event
+    #[arg(long, global = true, value_name = "TIME")]
+    pub syslog_reference: Option<DateTime<Utc>>,
+
+    /// Apdex threshold T in milliseconds (satisfied <= T, tolerating <= 4T)
+    #[arg(long, value_name = "MS")]
+    pub apdex_threshold: Option<u64>,
+
+    /// Include per-action event counts and p95 duration in the summary
+    #[arg(long)]
+    pub by_action: bool,
+
+    /// Report duplicate, out-of-order and gap counts in a data_quality section
+    #[arg(long)]
+    pub data_quality: bool,
+
+    /// Report gaps between consecutive events larger than this (e.g. 5m). Implies --data-quality
+    #[arg(long, value_parser = humantime::parse_duration)]
+    pub max_gap: Option<Duration>,
+
+    /// Exclude exact duplicate events from the aggregation. Implies --data-quality
+    #[arg(long)]
+    pub dedupe: bool,
+
+    /// Alternative report mode. If omitted, prints the summary report
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+/// Supported input formats
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum InputFormat {
+    /// JSON Lines, one event object per line
+    #[default]
+    Json,
+    /// Concatenated or pretty-printed JSON objects and JSON arrays of objects
+    JsonStream,
+    /// Comma separated values with a header row
+    Csv,
+    /// Tab separated values with a header row
+    Tsv,
+    /// logfmt key=value pairs, one event per line
+    Logfmt,
+    /// nginx combined access log (or a custom --log-format)
+    NginxCombined,
+    /// Apache Common Log Format (or a custom --log-format)
+    ApacheCommon,
+    /// Syslog, RFC 5424 or legacy RFC 3164
+    Syslog,
+    /// OpenTelemetry OTLP-JSON file export of logs and/or spans, one per line or pretty-printed
+    OtlpJson,
+    /// MessagePack records, each prefixed by a 4-byte big-endian length
+    Msgpack,
+    /// CBOR records, each prefixed by a 4-byte big-endian length
+    Cbor,
+}
+
+impl InputFormat {
+    /// Short name used in log messages
+    pub fn name(&self) -> &'static str {
+        match self {
+            InputFormat::Json => "JSON",
+            InputFormat::JsonStream => "JSON record",
+            InputFormat::Csv => "CSV",
+            InputFormat::Tsv => "TSV",
+            InputFormat::Logfmt => "logfmt",
+            InputFormat::NginxCombined => "nginx access log",
+            InputFormat::ApacheCommon => "apache access log",
+            InputFormat::Syslog => "syslog",
+            InputFormat::OtlpJson => "OTLP JSON",
+            InputFormat::Msgpack => "MessagePack",
+            InputFormat::Cbor => "CBOR",
+        }
+    }
+}
+
+/// Supported output formats
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    /// JSON, pretty-printed with --pretty
+    #[default]
+    Json,
+    /// MessagePack (map encoding with field names)
+    Msgpack,
+    /// CBOR
+    Cbor,
+}
+
+/// Keys events can be sampled by
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum SampleKey {
+    /// All events of a user are kept or dropped together
+    User,
+}
+
+#[derive(Subcommand)]
+pub enum Command {
+    /// Groups each user's events into sessions by inactivity gap
+    Sessions(SessionsArgs),
+    /// Reports how many users reached each step in order
+    Funnel(FunnelArgs),
+    /// Compares two logs or saved summaries and gates on regressions
+    Diff(DiffArgs),
+}
+
+#[derive(Args)]
+pub struct SessionsArgs {
+    /// Inactivity gap that starts a new session (e.g. 30m, 1h, 90s)
+    #[arg(long, default_value = "30m", value_parser = humantime::parse_duration)]
+    pub session_gap: Duration,
+}
+
+#[derive(Args)]
+pub struct FunnelArgs {
+    /// Comma separated actions in funnel order (e.g. login,run_script,logout)
+    #[arg(long, required = true, value_delimiter = ',')]
+    pub steps: Vec<String>,
+
+    /// Maximum time from the first to the last step (e.g. 1h). Unbounded if omitted
+    #[arg(long, value_parser = humantime::parse_duration)]
+    pub window: Option<Duration>,
+
+    /// Also report action -> next action transition counts
+    #[arg(long)]
+    pub transitions: bool,
+}
+
+#[derive(Args)]
+pub struct DiffArgs {
+    /// Baseline event log (JSON Lines) or saved summary (JSON)
+    #[arg(long)]
+    pub baseline: String,
+
+    /// Candidate event log (JSON Lines) or saved summary (JSON)
+    #[arg(long)]
+    pub candidate: String,
+
+    /// Maximum allowed p95 increase in percent, overall and per action
+    #[arg(long, default_value_t = 10.0, value_name = "PCT")]
+    pub max_p95_increase_pct: f64,
+
+    /// Maximum allowed absolute ERROR rate increase (e.g. 0.01 = 1 percentage point)
+    #[arg(long, default_value_t = 0.01, value_name = "RATE")]
+    pub max_error_rate_increase: f64,
+
+    /// Minimum events per action on both sides before its p95 is gated
+    #[arg(long, default_value_t = 30, value_name = "N")]
+    pub min_events: usize,
+}
+
+impl DiffArgs {
+    /// Builds the regression gate thresholds
+    pub fn thresholds(&self) -> Thresholds {
+        Thresholds {
+            max_p95_increase_pct: self.max_p95_increase_pct,
+            max_error_rate_increase: self.max_error_rate_increase,
+            min_events: self.min_events,
+        }
+    }
+}
+
+/// Parses a ratio between 0 and 1
+fn parse_ratio(s: &str) -> Result<f64, String> {
+    let ratio: f64 = s.parse().map_err(|_| format!("invalid ratio '{}'", s))?;
+    if !(0.0..=1.0).contains(&ratio) {
+        return Err(format!("ratio must be between 0 and 1, got {}", s));
+    }
+    Ok(ratio)
+}
+
+impl Cli {
+    /// Builds the processing options for App
+    /// Returns an error if the field mapping file cannot be read
+    pub fn options(&self) -> Result<Options, String> {
+        let field_mapping = if self.map_file.is_none() && self.map.is_empty() {
+            None
+        } else {
+            // Command line mappings override the file
+            let mut mapping = match self.map_file {
+                Some(ref path) => FieldMapping::from_toml_file(path)?,
+                None => FieldMapping::default(),
+            };
+            for field_path in &self.map {
+                mapping.insert(field_path.clone());
+            }
+            Some(mapping)
+        };
+
+        let rules = match self.rules {
+            Some(ref path) => {
+                let mut rules = RuleSet::from_toml_file(path)?;
+                for name in &self.disable_rule {
+                    rules.disable(name)?;
+                }
+                Some(rules)
+            }
+            None => None,
+        };
+
+        let sampling = match (self.sample, self.sample_n) {
+            (Some(0.0), _) => return Err("--sample must be greater than 0".to_string()),
+            (Some(rate), _) if self.sample_by == Some(SampleKey::User) => Some(SampleMode::ByUser(rate)),
+            (Some(rate), _) => Some(SampleMode::Bernoulli(rate)),
+            (None, Some(0)) => return Err("--sample-n must be greater than 0".to_string()),
+            (None, Some(size)) => Some(SampleMode::Reservoir(size)),
+            (None, None) => None,
+        };
+        // Sessions and funnels need every event of a user, a sample would silently undercount them
+        if sampling.is_some() && matches!(self.command, Some(Command::Sessions(_) | Command::Funnel(_))) {
+            return Err("--sample and --sample-n cannot be used with sessions or funnel".to_string());
+        }
+
+        if self.examples == Some(0) {
+            return Err("--examples must be greater than 0".to_string());
+        }
+
+        Ok(Options {
+            apdex_threshold: self.apdex_threshold,
+            by_action: self.by_action,
+            data_quality: self.data_quality || self.max_gap.is_some() || self.dedupe,
+            max_gap: self.max_gap,
+            dedupe: self.dedupe,
+            format: self.format,
+            log_format: self.log_format.clone(),
+            syslog_duration_key: self.syslog_duration_key.clone(),
+            syslog_reference: self.syslog_reference,
+            pattern: self.pattern.clone(),
+            field_mapping,
+            lenient: self.lenient,
+            field_stats: self.field_stats.clone(),
+            rules,
+            strict: self.strict,
+            max_bad_ratio: self.max_bad_ratio,
+            max_bad_lines: self.max_bad_lines,
+            max_line_bytes: self.max_line_bytes,
+            blank_lines_bad: self.blank_lines_bad,
+            sampling,
+            sample_seed: self.sample_seed,
+            examples: self.examples,
+            level_scale: match self.level_scale {
+                Some(ref path) => LevelScale::from_toml_file(path)?,
+                None => LevelScale::default(),
+            },
+            schema: self.schema.as_deref().map(EventSchema::from_file).transpose()?,
+            otlp: OtlpOptions {
+                user_attribute: self.otlp_user_attr.clone(),
+                action_attribute: self.otlp_action_attr.clone(),
+                duration_attribute: self.otlp_duration_attr.clone(),
+            },
+        })
+    }
 }
 
 #[cfg(test)]
@@ -23,7 +400,7 @@ mod tests {
     #[test]
     fn test_help_flag() {
         let output = Command::new("cargo")
-            .args(&["run", "--", "--help"])
+            .args(["run", "--", "--help"])
             .output()
             .expect("Failed to execute command");
         
@@ -36,7 +413,7 @@ mod tests {
     #[test]
     fn test_file_not_found() {
         let output = Command::new("cargo")
-            .args(&["run", "--", "--input", "nonexistent_file.json"])
+            .args(["run", "--", "--input", "nonexistent_file.json"])
             .output()
             .expect("Failed to execute command");
         
@@ -53,27 +430,145 @@ mod tests {
         let mut file = File::create(test_file).expect("Failed to create test file");
         writeln!(file, r#"{{"ts":"2026-01-19T12:00:01Z","level":"INFO","user":"alice","action":"test","duration_ms":100}}"#)
             .expect("Failed to write test data");
-        writeln!(file, "").expect("Failed to write blank line");
+        writeln!(file).expect("Failed to write blank line");
         writeln!(file, r#"{{"ts":"2026-01-19T12:00:02Z","level":"WARN","user":"bob","action":"test2","duration_ms":200}}"#)
             .expect("Failed to write test data");
         drop(file);
         
         let output = Command::new("cargo")
-            .args(&["run", "--", "--input", test_file])
+            .args(["run", "--", "--input", test_file])
             .output()
             .expect("Failed to execute command");
         
         // Should exit with code 0
         assert_eq!(output.status.code(), Some(0));
+
+        // The blank line is ignored, not counted as bad
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        assert!(stdout.contains(r#""total_lines":2,"bad_lines":0,"blank_lines":1"#));
+
+        let output = Command::new("cargo")
+            .args(["run", "--", "--input", test_file, "--blank-lines-bad"])
+            .output()
+            .expect("Failed to execute command");
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        assert!(stdout.contains(r#""total_lines":3,"bad_lines":1,"blank_lines":1"#));
         
         // Clean up
         std::fs::remove_file(test_file).ok();
     }
 
+    #[test]
+    fn test_sessions_mode() {
+        let output = Command::new("cargo")
+            .args(["run", "--", "sessions", "--input", "mock_data/test.jsonl", "--session-gap", "1h"])
+            .output()
+            .expect("Failed to execute command");
+
+        assert_eq!(output.status.code(), Some(0));
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        assert!(stdout.contains("\"sessions\":3"));
+    }
+
+    #[test]
+    fn test_strict_and_bad_line_limit_exit_codes() {
+        let path = "/tmp/eventsum_bad_lines.jsonl";
+        let mut file = File::create(path).expect("Failed to create test file");
+        writeln!(file, r#"{{"ts":"2026-01-19T12:00:01Z","level":"INFO","user":"alice","action":"test","duration_ms":100}}"#)
+            .expect("Failed to write test data");
+        writeln!(file, "not json").expect("Failed to write test data");
+
+        // Should exit with code 4 without a summary
+        let output = Command::new("cargo")
+            .args(["run", "--", "--input", path, "--strict"])
+            .output()
+            .expect("Failed to execute command");
+        assert_eq!(output.status.code(), Some(4));
+        assert!(output.stdout.is_empty());
+
+        // Should print the summary and exit with code 5
+        let output = Command::new("cargo")
+            .args(["run", "--", "--input", path, "--max-bad-lines", "0"])
+            .output()
+            .expect("Failed to execute command");
+        assert_eq!(output.status.code(), Some(5));
+        assert!(String::from_utf8_lossy(&output.stdout).contains("\"bad_lines\":1"));
+
+        // Within the ratio budget
+        let output = Command::new("cargo")
+            .args(["run", "--", "--input", path, "--max-bad-ratio", "0.5"])
+            .output()
+            .expect("Failed to execute command");
+        assert_eq!(output.status.code(), Some(0));
+
+        // Clean up
+        std::fs::remove_file(path).ok();
+    }
+
+    #[test]
+    fn test_diff_mode_regression_exit_code() {
+        let baseline = "/tmp/eventsum_diff_baseline.jsonl";
+        let candidate = "/tmp/eventsum_diff_candidate.jsonl";
+        let mut file = File::create(baseline).expect("Failed to create test file");
+        writeln!(file, r#"{{"ts":"2026-01-19T12:00:01Z","level":"INFO","user":"alice","action":"test","duration_ms":100}}"#)
+            .expect("Failed to write test data");
+        let mut file = File::create(candidate).expect("Failed to create test file");
+        writeln!(file, r#"{{"ts":"2026-01-19T12:00:01Z","level":"INFO","user":"alice","action":"test","duration_ms":500}}"#)
+            .expect("Failed to write test data");
+
+        let output = Command::new("cargo")
+            .args(["run", "--", "diff", "--baseline", baseline, "--candidate", candidate])
+            .output()
+            .expect("Failed to execute command");
+
+        // Should exit with code 3 (regression gate failed)
+        assert_eq!(output.status.code(), Some(3));
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        assert!(stdout.contains("\"passed\":false"));
+
+        // Same file on both sides passes
+        let output = Command::new("cargo")
+            .args(["run", "--", "diff", "--baseline", baseline, "--candidate", baseline])
+            .output()
+            .expect("Failed to execute command");
+        assert_eq!(output.status.code(), Some(0));
+
+        // Clean up
+        std::fs::remove_file(baseline).ok();
+        std::fs::remove_file(candidate).ok();
+    }
+
+    #[test]
+    fn test_sampling_rejected_for_sessions_and_funnel() {
+        use super::Cli;
+        use clap::Parser;
+
+        for args in [
+            vec!["eventsum", "--sample", "0.5", "sessions"],
+            vec!["eventsum", "--sample-n", "10", "funnel", "--steps", "login,logout"],
+        ] {
+            let cli = Cli::try_parse_from(&args).unwrap();
+            assert!(cli.options().unwrap_err().contains("sessions or funnel"));
+        }
+        assert!(Cli::try_parse_from(["eventsum", "--sample", "0.5"]).unwrap().options().is_ok());
+    }
+
+    #[test]
+    fn test_csv_format() {
+        let output = Command::new("cargo")
+            .args(["run", "--", "--format", "csv", "--input", "mock_data/test.csv"])
+            .output()
+            .expect("Failed to execute command");
+
+        assert_eq!(output.status.code(), Some(0));
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        assert!(stdout.contains("\"bad_lines\":1,\"blank_lines\":0,\"events\":5"));
+    }
+
     #[test]
     fn test_read_from_stdin() {
         let mut child = Command::new("cargo")
-            .args(&["run"])
+            .args(["run"])
             .stdin(std::process::Stdio::piped())
             .stdout(std::process::Stdio::piped())
             .stderr(std::process::Stdio::piped())
@@ -0,0 +1,436 @@
+use crate::app::{App, Options};
+use crate::result::{SummaryResult, UserCount};
+use crate::severity::LevelScale;
+use log::info;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader};
+
+/// z-score above which an error rate increase counts as significant (~95% one-sided)
+const SIGNIFICANT_Z: f64 = 1.645;
+
+/// Regression gate thresholds
+#[derive(Debug, Clone)]
+pub struct Thresholds {
+    /// Maximum allowed p95 increase in percent, overall and per action
+    pub max_p95_increase_pct: f64,
+    /// Maximum allowed error rate increase (ERROR events / events), absolute
+    pub max_error_rate_increase: f64,
+    /// Minimum events on both sides before a per-action p95 is compared
+    pub min_events: usize,
+}
+
+/// Baseline and candidate value of a counter
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Delta {
+    pub baseline: u64,
+    pub candidate: u64,
+    pub delta: i64,
+    /// Relative change in percent. None if the baseline is 0
+    pub change_pct: Option<f64>,
+}
+
+impl Delta {
+    fn new(baseline: u64, candidate: u64) -> Self {
+        let delta = (candidate as i128 - baseline as i128).clamp(i64::MIN as i128, i64::MAX as i128);
+        Delta {
+            baseline,
+            candidate,
+            delta: delta as i64,
+            change_pct: (baseline > 0).then(|| delta as f64 * 100.0 / baseline as f64),
+        }
+    }
+}
+
+/// Baseline and candidate ERROR rate with a two-proportion z-test
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RateDelta {
+    pub baseline: f64,
+    pub candidate: f64,
+    pub delta: f64,
+    /// Two-proportion z-score of the increase. 0 if not computable
+    pub z_score: f64,
+}
+
+impl RateDelta {
+    fn new(baseline_hits: usize, baseline_n: usize, candidate_hits: usize, candidate_n: usize) -> Self {
+        let rate = |hits: usize, n: usize| if n == 0 { 0.0 } else { hits as f64 / n as f64 };
+        let baseline = rate(baseline_hits, baseline_n);
+        let candidate = rate(candidate_hits, candidate_n);
+
+        // Pooled standard error of the difference
+        let pooled = rate(baseline_hits + candidate_hits, baseline_n + candidate_n);
+        let z_score = if baseline_n == 0 || candidate_n == 0 {
+            0.0
+        } else {
+            let se = (pooled * (1.0 - pooled) * (1.0 / baseline_n as f64 + 1.0 / candidate_n as f64)).sqrt();
+            if se > 0.0 { (candidate - baseline) / se } else { 0.0 }
+        };
+
+        RateDelta {
+            baseline,
+            candidate,
+            delta: candidate - baseline,
+            z_score,
+        }
+    }
+}
+
+/// Changes in the top users ranking
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TopUsersDiff {
+    pub baseline: Vec<UserCount>,
+    pub candidate: Vec<UserCount>,
+    /// Users only in the candidate ranking
+    pub entered: Vec<String>,
+    /// Users only in the baseline ranking
+    pub left: Vec<String>,
+}
+
+/// Per-action change in event count and p95
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ActionDiff {
+    pub events: Delta,
+    pub p95_duration_ms: Delta,
+}
+
+/// A failed regression gate
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Regression {
+    /// Metric that regressed, e.g. p95_duration_ms or by_action.login.p95_duration_ms
+    pub metric: String,
+    pub message: String,
+}
+
+/// Comparison of a baseline and a candidate summary
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DiffReport {
+    pub total_lines: Delta,
+    pub bad_lines: Delta,
+    pub events: Delta,
+    pub by_level: BTreeMap<String, Delta>,
+    pub error_rate: RateDelta,
+    pub p95_duration_ms: Delta,
+    pub top_users: TopUsersDiff,
+    /// Only actions present in both summaries. Empty if a side has no by_action section
+    pub by_action: BTreeMap<String, ActionDiff>,
+    pub regressions: Vec<Regression>,
+    /// True if no regression gate failed
+    pub passed: bool,
+}
+
+/// Returns true if the first non-whitespace byte of the file is `{`
+fn starts_with_object(path: &str) -> io::Result<bool> {
+    let mut reader = BufReader::new(File::open(path)?);
+    loop {
+        let buf = reader.fill_buf()?;
+        let Some(position) = buf.iter().position(|b| !b.is_ascii_whitespace()) else {
+            if buf.is_empty() {
+                return Ok(false);
+            }
+            let len = buf.len();
+            reader.consume(len);
+            continue;
+        };
+        return Ok(buf[position] == b'{');
+    }
+}
+
+/// Loads a summary from a saved summary JSON file or from an event log in the input format
+/// Only files starting with a JSON object are tried as a summary, reading a single value.
+/// Returns an error if the file cannot be read
+pub fn load_summary(path: &str, options: &Options) -> io::Result<SummaryResult> {
+    if starts_with_object(path)?
+        && let Ok(summary) = serde_json::from_reader::<_, SummaryResult>(BufReader::new(File::open(path)?))
+    {
+        info!("Loaded saved summary from {}", path);
+        return Ok(summary);
+    }
+
+    // Diff compares complete inputs, strict mode does not apply
+    let mut options = options.clone();
+    options.by_action = true;
+    options.strict = false;
+
+    let mut app = App::new(options);
+    app.read_from_file(path)?;
+    app.finalize();
+    Ok(app.into_result())
+}
+
+/// Returns the p95 regression message if the increase exceeds the threshold
+fn p95_regression(delta: &Delta, max_increase_pct: f64) -> Option<String> {
+    let exceeded = match delta.change_pct {
+        Some(pct) => pct > max_increase_pct,
+        // Any increase from 0 is unbounded
+        None => delta.candidate > 0,
+    };
+
+    exceeded.then(|| {
+        format!(
+            "p95 increased from {}ms to {}ms (limit +{}%)",
+            delta.baseline, delta.candidate, max_increase_pct
+        )
+    })
+}
+
+impl DiffReport {
+    /// Compares candidate against baseline and evaluates the regression gates
+    pub fn compare(
+        baseline: &SummaryResult,
+        candidate: &SummaryResult,
+        thresholds: &Thresholds,
+        scale: &LevelScale,
+    ) -> Self {
+        let mut regressions = Vec::new();
+
+        // Every level present on either side
+        let mut by_level = BTreeMap::new();
+        for (name, _) in baseline.by_level.ordered().into_iter().chain(candidate.by_level.ordered()) {
+            let delta = Delta::new(baseline.by_level.get(name) as u64, candidate.by_level.get(name) as u64);
+            by_level.insert(name.to_string(), delta);
+        }
+
+        let error_rate = RateDelta::new(
+            baseline.by_level.errors(scale),
+            baseline.events,
+            candidate.by_level.errors(scale),
+            candidate.events,
+        );
+        if error_rate.delta > thresholds.max_error_rate_increase && error_rate.z_score > SIGNIFICANT_Z {
+            regressions.push(Regression {
+                metric: "error_rate".to_string(),
+                message: format!(
+                    "error rate increased from {:.4} to {:.4} (limit +{}, z={:.2})",
+                    error_rate.baseline, error_rate.candidate, thresholds.max_error_rate_increase, error_rate.z_score
+                ),
+            });
+        }
+
+        let p95_duration_ms = Delta::new(baseline.p95_duration_ms, candidate.p95_duration_ms);
+        if let Some(message) = p95_regression(&p95_duration_ms, thresholds.max_p95_increase_pct) {
+            regressions.push(Regression {
+                metric: "p95_duration_ms".to_string(),
+                message,
+            });
+        }
+
+        let mut by_action = BTreeMap::new();
+        if let (Some(base_actions), Some(cand_actions)) = (&baseline.by_action, &candidate.by_action) {
+            for (action, base) in base_actions {
+                let Some(cand) = cand_actions.get(action) else {
+                    continue;
+                };
+
+                let diff = ActionDiff {
+                    events: Delta::new(base.events as u64, cand.events as u64),
+                    p95_duration_ms: Delta::new(base.p95_duration_ms, cand.p95_duration_ms),
+                };
+
+                // Too few samples to call a p95 change meaningful
+                let enough_events = base.events >= thresholds.min_events && cand.events >= thresholds.min_events;
+                if enough_events
+                    && let Some(message) = p95_regression(&diff.p95_duration_ms, thresholds.max_p95_increase_pct)
+                {
+                    regressions.push(Regression {
+                        metric: format!("by_action.{}.p95_duration_ms", action),
+                        message,
+                    });
+                }
+
+                by_action.insert(action.clone(), diff);
+            }
+        }
+
+        let names = |users: &[UserCount]| users.iter().map(|u| u.user.clone()).collect::<Vec<_>>();
+        let base_users = names(&baseline.top_users);
+        let cand_users = names(&candidate.top_users);
+        let top_users = TopUsersDiff {
+            baseline: baseline.top_users.clone(),
+            candidate: candidate.top_users.clone(),
+            entered: cand_users.iter().filter(|u| !base_users.contains(u)).cloned().collect(),
+            left: base_users.iter().filter(|u| !cand_users.contains(u)).cloned().collect(),
+        };
+
+        DiffReport {
+            total_lines: Delta::new(baseline.total_lines as u64, candidate.total_lines as u64),
+            bad_lines: Delta::new(baseline.bad_lines as u64, candidate.bad_lines as u64),
+            events: Delta::new(baseline.events as u64, candidate.events as u64),
+            by_level,
+            error_rate,
+            p95_duration_ms,
+            top_users,
+            by_action,
+            passed: regressions.is_empty(),
+            regressions,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::binary::BinaryFormat;
+    use crate::cli::InputFormat;
+    use crate::event::{Event, Level};
+    use crate::result::ActionStats;
+
+    fn thresholds() -> Thresholds {
+        Thresholds {
+            max_p95_increase_pct: 10.0,
+            max_error_rate_increase: 0.01,
+            min_events: 30,
+        }
+    }
+
+    fn compare(baseline: &SummaryResult, candidate: &SummaryResult) -> DiffReport {
+        DiffReport::compare(baseline, candidate, &thresholds(), &LevelScale::default())
+    }
+
+    fn summary(events: usize, errors: usize, p95: u64) -> SummaryResult {
+        let mut result = SummaryResult::new();
+        result.total_lines = events;
+        result.events = events;
+        result.by_level.add(&Level::Info, events - errors);
+        result.by_level.add(&Level::Error, errors);
+        result.p95_duration_ms = p95;
+        result
+    }
+
+    #[test]
+    fn test_delta() {
+        let delta = Delta::new(200, 250);
+        assert_eq!(delta.delta, 50);
+        assert_eq!(delta.change_pct, Some(25.0));
+
+        let delta = Delta::new(0, 5);
+        assert_eq!(delta.change_pct, None);
+
+        let delta = Delta::new(u64::MAX, 0);
+        assert_eq!(delta.delta, i64::MIN);
+    }
+
+    #[test]
+    fn test_diff_no_regression() {
+        let report = compare(&summary(1000, 10, 100), &summary(1000, 10, 105));
+
+        assert!(report.passed);
+        assert!(report.regressions.is_empty());
+        assert_eq!(report.p95_duration_ms.delta, 5);
+    }
+
+    #[test]
+    fn test_diff_p95_regression() {
+        let report = compare(&summary(1000, 10, 100), &summary(1000, 10, 150));
+
+        assert!(!report.passed);
+        assert_eq!(report.regressions[0].metric, "p95_duration_ms");
+    }
+
+    #[test]
+    fn test_diff_error_rate_significance() {
+        // 1% -> 5% over 1000 events each is significant
+        let report = compare(&summary(1000, 10, 100), &summary(1000, 50, 100));
+        assert!(report.regressions.iter().any(|r| r.metric == "error_rate"));
+
+        // 0 -> 1 error over 20 events exceeds the delta but is not significant
+        let report = compare(&summary(20, 0, 100), &summary(20, 1, 100));
+        assert!(report.passed);
+    }
+
+    #[test]
+    fn test_diff_by_action_min_events() {
+        let mut baseline = summary(100, 0, 100);
+        let mut candidate = summary(100, 0, 100);
+        let action = |events, p95| ActionStats {
+            events,
+            p95_duration_ms: p95,
+        };
+        baseline.by_action = Some(BTreeMap::from([
+            ("login".to_string(), action(50, 10)),
+            ("rare".to_string(), action(2, 10)),
+        ]));
+        candidate.by_action = Some(BTreeMap::from([
+            ("login".to_string(), action(50, 20)),
+            ("rare".to_string(), action(2, 100)),
+        ]));
+
+        let report = compare(&baseline, &candidate);
+
+        assert_eq!(report.by_action.len(), 2);
+        assert_eq!(report.regressions.len(), 1);
+        assert_eq!(report.regressions[0].metric, "by_action.login.p95_duration_ms");
+    }
+
+    #[test]
+    fn test_diff_top_users() {
+        let mut baseline = summary(10, 0, 100);
+        let mut candidate = summary(10, 0, 100);
+        let user = |name: &str| UserCount {
+            user: name.to_string(),
+            count: 1,
+        };
+        baseline.top_users = vec![user("alice"), user("bob")];
+        candidate.top_users = vec![user("alice"), user("carol")];
+
+        let report = compare(&baseline, &candidate);
+
+        assert_eq!(report.top_users.entered, vec!["carol".to_string()]);
+        assert_eq!(report.top_users.left, vec!["bob".to_string()]);
+    }
+
+    #[test]
+    fn test_load_msgpack_log() {
+        let path = "/tmp/eventsum_diff_log.bin";
+        let format = BinaryFormat::MessagePack;
+        let mut stream = Vec::new();
+        for user in ["alice", "bob"] {
+            let json = format!(
+                r#"{{"ts":"2026-01-19T12:00:01Z","level":"INFO","user":"{}","action":"login","duration_ms":10}}"#,
+                user
+            );
+            let record = format.encode(&Event::from_json_line(&json, &LevelScale::default()).unwrap()).unwrap();
+            stream.extend_from_slice(&(record.len() as u32).to_be_bytes());
+            stream.extend_from_slice(&record);
+        }
+        std::fs::write(path, stream).unwrap();
+        let options = Options {
+            format: InputFormat::Msgpack,
+            ..Options::default()
+        };
+
+        let summary = load_summary(path, &options).unwrap();
+
+        assert_eq!(summary.events, 2);
+        assert_eq!(summary.bad_lines, 0);
+        std::fs::remove_file(path).ok();
+    }
+
+    #[test]
+    fn test_load_log_with_invalid_utf8() {
+        let path = "/tmp/eventsum_diff_utf8.jsonl";
+        let mut content =
+            br#"{"ts":"2026-01-19T12:00:01Z","level":"INFO","user":"alice","action":"login","duration_ms":10}"#.to_vec();
+        content.extend_from_slice(b"\nbad \xff line\n");
+        std::fs::write(path, content).unwrap();
+
+        let summary = load_summary(path, &Options::default()).unwrap();
+
+        assert_eq!(summary.events, 1);
+        assert_eq!(summary.bad_lines, 1);
+        std::fs::remove_file(path).ok();
+    }
+
+    #[test]
+    fn test_load_saved_summary() {
+        let path = "/tmp/eventsum_diff_summary.json";
+        std::fs::write(path, serde_json::to_string_pretty(&summary(10, 1, 100)).unwrap()).unwrap();
+
+        let loaded = load_summary(path, &Options::default()).unwrap();
+
+        assert_eq!(loaded.events, 10);
+        assert_eq!(loaded.p95_duration_ms, 100);
+        std::fs::remove_file(path).ok();
+    }
+}
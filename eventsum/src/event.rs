@@ -1,17 +1,151 @@
-use serde::{Deserialize, Serialize};
-use log::{warn, error};
+use crate::logfmt;
+use crate::severity::LevelScale;
+use chrono::{DateTime, Utc};
+use serde::de::value::MapAccessDeserializer;
+use serde::de::{self, MapAccess, SeqAccess, Visitor};
+use serde::{Deserialize, Deserializer, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::str::FromStr;
+use log::warn;
+
+/// Abbreviated or alternative level names, accepted by --lenient and grok patterns
+const LEVEL_ALIASES: &[(&str, Level)] = &[
+    ("WARNING", Level::Warn),
+    ("ERR", Level::Error),
+    ("CRIT", Level::Critical),
+    ("EMERG", Level::Emergency),
+];
 
 /// Log level for events
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
-#[serde(rename_all = "UPPERCASE")]
+/// Builtin levels cover the common log4j and syslog names, custom levels come from
+/// a user-defined severity scale
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
 pub enum Level {
+    Trace,
+    Debug,
     Info,
+    Notice,
     Warn,
     Error,
+    Critical,
+    Alert,
+    Emergency,
+    Fatal,
+    /// Level of a custom scale, name in upper case
+    Custom { name: String, severity: u8 },
+}
+
+impl Level {
+    /// Builtin levels in order of severity
+    pub const BUILTIN: [Level; 10] = [
+        Level::Trace,
+        Level::Debug,
+        Level::Info,
+        Level::Notice,
+        Level::Warn,
+        Level::Error,
+        Level::Critical,
+        Level::Alert,
+        Level::Emergency,
+        Level::Fatal,
+    ];
+
+    /// Upper case name, as used in the input and in by_level
+    pub fn name(&self) -> &str {
+        match self {
+            Level::Trace => "TRACE",
+            Level::Debug => "DEBUG",
+            Level::Info => "INFO",
+            Level::Notice => "NOTICE",
+            Level::Warn => "WARN",
+            Level::Error => "ERROR",
+            Level::Critical => "CRITICAL",
+            Level::Alert => "ALERT",
+            Level::Emergency => "EMERGENCY",
+            Level::Fatal => "FATAL",
+            Level::Custom { name, .. } => name,
+        }
+    }
+
+    /// Severity on the OpenTelemetry scale: TRACE 1, DEBUG 5, INFO 9, WARN 13, ERROR 17, FATAL 21
+    /// NOTICE, CRITICAL, ALERT and EMERGENCY sit next to their closest OpenTelemetry level
+    pub fn severity(&self) -> u8 {
+        match self {
+            Level::Trace => 1,
+            Level::Debug => 5,
+            Level::Info => 9,
+            Level::Notice => 10,
+            Level::Warn => 13,
+            Level::Error => 17,
+            Level::Critical => 18,
+            Level::Alert => 19,
+            Level::Emergency | Level::Fatal => 21,
+            Level::Custom { severity, .. } => *severity,
+        }
+    }
+
+    /// True for ERROR and anything more severe, including custom levels
+    pub fn is_error(&self) -> bool {
+        self.severity() >= Level::Error.severity()
+    }
+
+    /// Resolves an abbreviated or alternative name (WARNING, ERR, CRIT, EMERG) case-insensitively
+    /// Used by --lenient and the LOGLEVEL grok pattern
+    pub fn from_alias(name: &str) -> Option<Level> {
+        let upper = name.to_ascii_uppercase();
+        LEVEL_ALIASES
+            .iter()
+            .find(|(alias, _)| *alias == upper)
+            .map(|(_, level)| level.clone())
+    }
+}
+
+impl fmt::Display for Level {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl From<Level> for String {
+    fn from(level: Level) -> Self {
+        level.name().to_string()
+    }
+}
+
+impl TryFrom<String> for Level {
+    type Error = String;
+
+    /// Deserialized names must be upper case, --lenient accepts any case
+    fn try_from(s: String) -> Result<Self, String> {
+        if s.chars().any(|c| c.is_ascii_lowercase()) {
+            return Err(format!("unknown level '{}'", s));
+        }
+        s.parse()
+    }
+}
+
+impl FromStr for Level {
+    type Err = String;
+
+    /// Parses a builtin level name case-insensitively (e.g. info, Info, INFO)
+    /// Custom levels are parsed with LevelScale::parse_level
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let upper = s.to_ascii_uppercase();
+        Level::BUILTIN
+            .iter()
+            .find(|level| level.name() == upper)
+            .cloned()
+            .ok_or_else(|| format!("unknown level '{}'", s))
+    }
 }
 
 /// Event structure representing a single log entry
-#[derive(Debug, Clone, Serialize, Deserialize)]
+///
+/// Deserializes like RawEvent, with builtin levels only
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
 pub struct Event {
     /// ISO-8601 timestamp (UTC)
     pub ts: String,
@@ -23,19 +157,176 @@ pub struct Event {
     pub action: String,
     /// Duration in milliseconds
     pub duration_ms: u64,
+    /// Unknown fields of the input, kept as is
+    #[serde(flatten, skip_serializing_if = "BTreeMap::is_empty")]
+    pub extra: BTreeMap<String, Value>,
+}
+
+/// Field names of Event, for deserializers that need them up front
+const EVENT_FIELDS: &[&str] = &["ts", "level", "user", "action", "duration_ms"];
+
+/// An Event as deserialized, with the level name not yet resolved against a scale
+///
+/// Deserializes from a map with field names (unknown fields go to extra) or from a
+/// sequence `[ts, level, user, action, duration_ms]`, the compact MessagePack encoding
+#[derive(Debug, Clone, PartialEq)]
+pub struct RawEvent {
+    ts: String,
+    level: String,
+    user: String,
+    action: String,
+    duration_ms: u64,
+    extra: BTreeMap<String, Value>,
+}
+
+/// Map form of RawEvent. flatten only works with maps, so sequences are handled by RawEventVisitor
+#[derive(Deserialize)]
+struct RawEventMap {
+    ts: String,
+    level: String,
+    user: String,
+    action: String,
+    duration_ms: u64,
+    #[serde(flatten)]
+    extra: BTreeMap<String, Value>,
+}
+
+/// Resolves a level name against the scale. Strict parsers require it in upper case
+pub fn strict_level(name: &str, scale: &LevelScale) -> Result<Level, String> {
+    if name.chars().any(|c| c.is_ascii_lowercase()) {
+        return Err(format!("unknown level '{}'", name));
+    }
+    scale.parse_level(name)
+}
+
+impl RawEvent {
+    /// Resolves the level name, which must be upper case, against the scale
+    pub fn resolve(self, scale: &LevelScale) -> Result<Event, String> {
+        Ok(Event {
+            level: strict_level(&self.level, scale)?,
+            ts: self.ts,
+            user: self.user,
+            action: self.action,
+            duration_ms: self.duration_ms,
+            extra: self.extra,
+        })
+    }
+}
+
+struct RawEventVisitor;
+
+impl<'de> Visitor<'de> for RawEventVisitor {
+    type Value = RawEvent;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("struct Event")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, map: A) -> Result<RawEvent, A::Error> {
+        let fields = RawEventMap::deserialize(MapAccessDeserializer::new(map))?;
+        Ok(RawEvent {
+            ts: fields.ts,
+            level: fields.level,
+            user: fields.user,
+            action: fields.action,
+            duration_ms: fields.duration_ms,
+            extra: fields.extra,
+        })
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<RawEvent, A::Error> {
+        let missing = |index| de::Error::invalid_length(index, &"5 elements");
+        let event = RawEvent {
+            ts: seq.next_element()?.ok_or_else(|| missing(0))?,
+            level: seq.next_element()?.ok_or_else(|| missing(1))?,
+            user: seq.next_element()?.ok_or_else(|| missing(2))?,
+            action: seq.next_element()?.ok_or_else(|| missing(3))?,
+            duration_ms: seq.next_element()?.ok_or_else(|| missing(4))?,
+            extra: BTreeMap::new(),
+        };
+        if seq.next_element::<de::IgnoredAny>()?.is_some() {
+            return Err(de::Error::invalid_length(6, &"5 elements"));
+        }
+        Ok(event)
+    }
+}
+
+impl<'de> Deserialize<'de> for RawEvent {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_struct("Event", EVENT_FIELDS, RawEventVisitor)
+    }
+}
+
+impl<'de> Deserialize<'de> for Event {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        RawEvent::deserialize(deserializer)?
+            .resolve(&LevelScale::default())
+            .map_err(de::Error::custom)
+    }
 }
 
 impl Event {
     /// Parses a JSON line into an Event
-    /// Returns None if parsing fails or validation fails
-    pub fn from_json_line(line: &str) -> Option<Self> {
-        match serde_json::from_str(line) {
-            Ok(event) => Some(event),
-            Err(e) => {
-                error!("Failed to parse JSON: {}", e);
-                None
+    /// Returns the reason if the line is not valid JSON or a field is missing
+    pub fn from_json_line(line: &str, scale: &LevelScale) -> Result<Self, String> {
+        serde_json::from_str::<RawEvent>(line)
+            .map_err(|e| format!("invalid JSON: {}", e))?
+            .resolve(scale)
+    }
+
+    /// Parses a CSV/TSV record into an Event, mapping columns by header name
+    /// Unknown columns are kept as extra fields. Returns the reason if a field is missing or malformed
+    pub fn from_csv_record(
+        record: &csv::StringRecord,
+        headers: &csv::StringRecord,
+        scale: &LevelScale,
+    ) -> Result<Self, String> {
+        record
+            .deserialize::<RawEvent>(Some(headers))
+            .map_err(|e| format!("invalid record: {}", e))?
+            .resolve(scale)
+    }
+
+    /// Parses a logfmt line (`ts=... level=info user=alice ...`) into an Event
+    /// Levels are case-insensitive, unknown keys are kept as string extra fields.
+    /// Returns the reason if the line is malformed or a required field is missing
+    pub fn from_logfmt_line(line: &str, scale: &LevelScale) -> Result<Self, String> {
+        let pairs = logfmt::parse_pairs(line)?;
+
+        let (mut ts, mut level, mut user, mut action, mut duration_ms) = (None, None, None, None, None);
+        let mut extra = BTreeMap::new();
+        for (key, value) in pairs {
+            match key.as_str() {
+                "ts" => ts = Some(value),
+                "level" => level = Some(value),
+                "user" => user = Some(value),
+                "action" => action = Some(value),
+                "duration_ms" => duration_ms = Some(value),
+                _ => {
+                    extra.insert(key, Value::String(value));
+                }
             }
         }
+
+        let (Some(ts), Some(level), Some(user), Some(action), Some(duration_ms)) =
+            (ts, level, user, action, duration_ms)
+        else {
+            return Err("missing required field".to_string());
+        };
+
+        let level = scale.parse_level(&level)?;
+        let duration_ms = duration_ms
+            .parse::<u64>()
+            .map_err(|e| format!("invalid duration_ms '{}': {}", duration_ms, e))?;
+
+        Ok(Event {
+            ts,
+            level,
+            user,
+            action,
+            duration_ms,
+            extra,
+        })
     }
 
     /// Validates that the event has non-empty required fields
@@ -59,8 +350,30 @@ impl Event {
         
         valid
     }
-    
-    
+
+    /// Parses the ts field as an RFC 3339 timestamp, normalized to UTC
+    /// Returns None if the timestamp cannot be parsed
+    pub fn timestamp(&self) -> Option<DateTime<Utc>> {
+        DateTime::parse_from_rfc3339(&self.ts)
+            .map(|ts| ts.with_timezone(&Utc))
+            .ok()
+    }
+}
+
+#[cfg(test)]
+impl Event {
+    /// Test event at 2026-01-19T12:00:01Z taking 1 ms, without extra fields
+    /// Other fields are set with struct update syntax, e.g. `Event { duration_ms: 5, ..Event::test(..) }`
+    pub fn test(user: &str, action: &str, level: Level) -> Event {
+        Event {
+            ts: "2026-01-19T12:00:01Z".to_string(),
+            level,
+            user: user.to_string(),
+            action: action.to_string(),
+            duration_ms: 1,
+            extra: BTreeMap::new(),
+        }
+    }
 }
 
 #[cfg(test)]
@@ -70,8 +383,8 @@ mod tests {
     #[test]
     fn test_valid_event_parsing() {
         let json = r#"{"ts":"2026-01-19T12:00:01Z","level":"INFO","user":"alice","action":"run_script","duration_ms":120}"#;
-        let event = Event::from_json_line(json);
-        assert!(event.is_some());
+        let event = Event::from_json_line(json, &LevelScale::default());
+        assert!(event.is_ok());
         let event = event.unwrap();
         assert_eq!(event.user, "alice");
         assert_eq!(event.level, Level::Info);
@@ -81,32 +394,133 @@ mod tests {
     #[test]
     fn test_invalid_json() {
         let json = "not-json";
-        let event = Event::from_json_line(json);
-        assert!(event.is_none());
+        let event = Event::from_json_line(json, &LevelScale::default());
+        assert!(event.is_err());
     }
 
     #[test]
     fn test_empty_user() {
         let json = r#"{"ts":"2026-01-19T12:00:01Z","level":"INFO","user":"","action":"run_script","duration_ms":120}"#;
-        let event = Event::from_json_line(json);
-        assert!(event.is_some());
+        let event = Event::from_json_line(json, &LevelScale::default());
+        assert!(event.is_ok());
         let event = event.unwrap();
         assert!(!event.is_valid()); // Validation should fail
     }
 
+    #[test]
+    fn test_timestamp_parsing() {
+        let json = r#"{"ts":"2026-01-19T14:00:01+02:00","level":"INFO","user":"alice","action":"test","duration_ms":1}"#;
+        let event = Event::from_json_line(json, &LevelScale::default()).unwrap();
+        assert_eq!(event.timestamp().unwrap().to_rfc3339(), "2026-01-19T12:00:01+00:00");
+
+        let json = r#"{"ts":"yesterday","level":"INFO","user":"alice","action":"test","duration_ms":1}"#;
+        let event = Event::from_json_line(json, &LevelScale::default()).unwrap();
+        assert!(event.timestamp().is_none());
+    }
+
+    #[test]
+    fn test_csv_record_parsing() {
+        let headers = csv::StringRecord::from(vec!["user", "extra", "ts", "level", "action", "duration_ms"]);
+        let record = csv::StringRecord::from(vec!["alice", "x", "2026-01-19T12:00:01Z", "WARN", "run, script", "120"]);
+
+        let event = Event::from_csv_record(&record, &headers, &LevelScale::default()).unwrap();
+        assert_eq!(event.user, "alice");
+        assert_eq!(event.level, Level::Warn);
+        assert_eq!(event.action, "run, script");
+        assert_eq!(event.duration_ms, 120);
+
+        let record = csv::StringRecord::from(vec!["alice", "x", "2026-01-19T12:00:01Z", "WARN", "run", "-1"]);
+        assert!(Event::from_csv_record(&record, &headers, &LevelScale::default()).is_err());
+    }
+
+    #[test]
+    fn test_logfmt_line_parsing() {
+        let line = r#"ts=2026-01-19T12:00:01Z level=warn user=alice action="run script" duration_ms=12 host=a"#;
+        let event = Event::from_logfmt_line(line, &LevelScale::default()).unwrap();
+        assert_eq!(event.level, Level::Warn);
+        assert_eq!(event.action, "run script");
+        assert_eq!(event.duration_ms, 12);
+
+        // Missing duration_ms
+        let line = "ts=2026-01-19T12:00:01Z level=info user=alice action=login";
+        assert!(Event::from_logfmt_line(line, &LevelScale::default()).is_err());
+
+        // Unknown level
+        let line = "ts=2026-01-19T12:00:01Z level=verbose user=alice action=login duration_ms=1";
+        assert!(Event::from_logfmt_line(line, &LevelScale::default()).is_err());
+    }
+
     #[test]
     fn test_all_levels() {
         let levels = vec![
             ("INFO", Level::Info),
             ("WARN", Level::Warn),
             ("ERROR", Level::Error),
+            ("TRACE", Level::Trace),
+            ("DEBUG", Level::Debug),
+            ("NOTICE", Level::Notice),
+            ("CRITICAL", Level::Critical),
+            ("ALERT", Level::Alert),
+            ("EMERGENCY", Level::Emergency),
+            ("FATAL", Level::Fatal),
         ];
         
         for (level_str, expected_level) in levels {
             let json = format!(r#"{{"ts":"2026-01-19T12:00:01Z","level":"{}","user":"alice","action":"test","duration_ms":100}}"#, level_str);
-            let event = Event::from_json_line(&json);
-            assert!(event.is_some());
+            let event = Event::from_json_line(&json, &LevelScale::default());
+            assert!(event.is_ok());
             assert_eq!(event.unwrap().level, expected_level);
         }
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_extra_fields() {
+        let json = r#"{"ts":"2026-01-19T12:00:01Z","level":"INFO","user":"alice","action":"run","duration_ms":1,"region":"eu","status_code":200}"#;
+        let event = Event::from_json_line(json, &LevelScale::default()).unwrap();
+
+        assert_eq!(event.extra["region"], "eu");
+        assert_eq!(event.extra["status_code"], 200);
+        // Extra fields are written back next to the known ones
+        let round_trip: serde_json::Value = serde_json::to_value(&event).unwrap();
+        assert_eq!(round_trip["region"], "eu");
+
+        let line = "ts=2026-01-19T12:00:01Z level=info user=alice action=login duration_ms=1 host=web1";
+        let event = Event::from_logfmt_line(line, &LevelScale::default()).unwrap();
+        assert_eq!(event.extra["host"], "web1");
+    }
+
+    #[test]
+    fn test_json_levels_are_upper_case() {
+        let json = r#"{"ts":"2026-01-19T12:00:01Z","level":"info","user":"alice","action":"test","duration_ms":1}"#;
+        assert!(Event::from_json_line(json, &LevelScale::default()).is_err());
+        assert_eq!("info".parse::<Level>().unwrap(), Level::Info);
+    }
+
+    #[test]
+    fn test_level_severity() {
+        assert!(Level::Debug.severity() < Level::Info.severity());
+        assert!(Level::Warn.severity() < Level::Error.severity());
+        assert!(!Level::Warn.is_error());
+        assert!(Level::Error.is_error());
+        assert!(Level::Fatal.is_error());
+
+        let custom = Level::Custom {
+            name: "SEVERE".to_string(),
+            severity: 18,
+        };
+        assert!(custom.is_error());
+        assert_eq!(serde_json::to_string(&custom).unwrap(), r#""SEVERE""#);
+    }
+
+    #[test]
+    fn test_custom_levels_use_the_given_scale() {
+        let json = r#"{"ts":"2026-01-19T12:00:01Z","level":"SEVERE","user":"alice","action":"test","duration_ms":1}"#;
+        let low = LevelScale::from_toml_str("SEVERE = 3").unwrap();
+        let high = LevelScale::from_toml_str("SEVERE = 18").unwrap();
+
+        assert!(!Event::from_json_line(json, &low).unwrap().level.is_error());
+        assert!(Event::from_json_line(json, &high).unwrap().level.is_error());
+        assert!(Event::from_json_line(json, &LevelScale::default()).is_err());
+        assert!(Event::from_logfmt_line("ts=t level=severe user=a action=b duration_ms=1", &high).is_ok());
+    }
+}
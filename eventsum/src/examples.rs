@@ -0,0 +1,210 @@
+use crate::event::Event;
+use crate::sampling::{Reservoir, SplitMix64};
+use crate::stats;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+
+/// Number of top actions that get examples, same as the number of top users
+const TOP_ACTIONS: usize = 3;
+
+/// A valid event with the line it was read from
+/// Holds the parsed event (including extra fields) rather than the raw line text, so the
+/// text of every aggregated line does not have to be kept until the summary is built
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Example {
+    pub line: usize,
+    #[serde(flatten)]
+    pub event: Event,
+}
+
+/// Example events per level, per top user and per top action, ordered by line
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Examples {
+    pub by_level: BTreeMap<String, Vec<Example>>,
+    pub by_user: BTreeMap<String, Vec<Example>>,
+    pub by_action: BTreeMap<String, Vec<Example>>,
+}
+
+/// Keeps a uniform sample of K events per level, user and action of the aggregated events
+///
+/// Reservoirs hold indices into the aggregated events and are only resolved to events at the
+/// end, so memory grows with K indices times the number of distinct users and actions
+pub struct ExampleCollector {
+    size: usize,
+    rng: SplitMix64,
+    by_level: HashMap<String, Reservoir<usize>>,
+    by_user: HashMap<String, Reservoir<usize>>,
+    by_action: HashMap<String, Reservoir<usize>>,
+}
+
+/// Offers an event index to the reservoir of a group, creating the reservoir if needed
+fn offer(groups: &mut HashMap<String, Reservoir<usize>>, key: &str, size: usize, rng: &mut SplitMix64, index: usize) {
+    if !groups.contains_key(key) {
+        groups.insert(key.to_string(), Reservoir::new(size));
+    }
+    if let Some(reservoir) = groups.get_mut(key) {
+        reservoir.offer_with(rng, || index);
+    }
+}
+
+/// Returns the examples of the given groups, ordered by line
+/// lines holds the line each event was read from
+fn resolve(
+    groups: &HashMap<String, Reservoir<usize>>,
+    keys: &[String],
+    events: &[Event],
+    lines: &[usize],
+) -> BTreeMap<String, Vec<Example>> {
+    keys.iter()
+        .filter_map(|key| groups.get(key).map(|reservoir| (key, reservoir)))
+        .map(|(key, reservoir)| {
+            let mut examples: Vec<Example> = reservoir
+                .items()
+                .iter()
+                .filter_map(|&index| {
+                    Some(Example {
+                        line: *lines.get(index)?,
+                        event: events.get(index)?.clone(),
+                    })
+                })
+                .collect();
+            examples.sort_by_key(|example| example.line);
+            (key.clone(), examples)
+        })
+        .collect()
+}
+
+impl ExampleCollector {
+    /// Creates a collector keeping size examples per group
+    pub fn new(size: usize, seed: u64) -> Self {
+        ExampleCollector {
+            size,
+            rng: SplitMix64::new(seed),
+            by_level: HashMap::new(),
+            by_user: HashMap::new(),
+            by_action: HashMap::new(),
+        }
+    }
+
+    /// Offers the aggregated event at index
+    pub fn offer(&mut self, index: usize, event: &Event) {
+        let size = self.size;
+        offer(&mut self.by_level, event.level.name(), size, &mut self.rng, index);
+        offer(&mut self.by_user, &event.user, size, &mut self.rng, index);
+        offer(&mut self.by_action, &event.action, size, &mut self.rng, index);
+    }
+
+    /// Returns the examples of every level, the given top users and the most frequent actions
+    /// events and lines are the offered events and the lines they were read from
+    pub fn examples(&self, events: &[Event], lines: &[usize], top_users: &[String]) -> Examples {
+        let levels: Vec<String> = self.by_level.keys().cloned().collect();
+        let action_counts: HashMap<String, usize> = self
+            .by_action
+            .iter()
+            .map(|(action, reservoir)| (action.clone(), reservoir.seen()))
+            .collect();
+        let top_actions: Vec<String> = stats::top_counts(&action_counts, TOP_ACTIONS)
+            .into_iter()
+            .map(|action| action.name)
+            .collect();
+
+        Examples {
+            by_level: resolve(&self.by_level, &levels, events, lines),
+            by_user: resolve(&self.by_user, top_users, events, lines),
+            by_action: resolve(&self.by_action, &top_actions, events, lines),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::event::Level;
+
+    #[test]
+    fn test_examples_per_group() {
+        let mut collector = ExampleCollector::new(2, 0);
+        let lines: Vec<usize> = (1..=100).collect();
+        let events: Vec<Event> = lines
+            .iter()
+            .map(|line| {
+                let level = if line % 10 == 0 { Level::Error } else { Level::Info };
+                let action = ["login", "run", "logout", "idle"][line % 4];
+                Event::test(if line % 2 == 0 { "alice" } else { "bob" }, action, level)
+            })
+            .collect();
+        for (index, event) in events.iter().enumerate() {
+            collector.offer(index, event);
+        }
+
+        let examples = collector.examples(&events, &lines, &["alice".to_string()]);
+
+        assert_eq!(examples.by_level["ERROR"].len(), 2);
+        assert!(examples.by_level["ERROR"].iter().all(|e| e.line % 10 == 0));
+        assert!(examples.by_level["INFO"][0].line < examples.by_level["INFO"][1].line);
+        assert_eq!(examples.by_user.keys().collect::<Vec<_>>(), vec!["alice"]);
+        assert!(examples.by_user["alice"].iter().all(|e| e.event.user == "alice"));
+        assert_eq!(examples.by_action.len(), 3);
+    }
+
+    #[test]
+    fn test_fewer_events_than_examples() {
+        let mut collector = ExampleCollector::new(5, 0);
+        let events = vec![Event::test("alice", "login", Level::Warn)];
+        collector.offer(0, &events[0]);
+
+        let examples = collector.examples(&events, &[3], &["alice".to_string(), "carol".to_string()]);
+
+        assert_eq!(examples.by_level["WARN"][0].line, 3);
+        assert!(!examples.by_user.contains_key("carol"));
+    }
+
+    #[test]
+    fn test_examples_come_from_the_sample() {
+        use crate::app::{App, Options};
+        use crate::sampling::SampleMode;
+        use std::io::Write;
+
+        let path = "/tmp/eventsum_examples_sample.jsonl";
+        let mut file = std::fs::File::create(path).unwrap();
+        for i in 0..50 {
+            let event = Event::test(&format!("user{}", i % 5), "login", Level::Info);
+            writeln!(file, "{}", serde_json::to_string(&event).unwrap()).unwrap();
+        }
+        drop(file);
+
+        let mut app = App::new(Options {
+            sampling: Some(SampleMode::Reservoir(1)),
+            examples: Some(3),
+            ..Options::default()
+        });
+        app.read_from_file(path).unwrap();
+        app.finalize();
+        std::fs::remove_file(path).ok();
+
+        // Only one event survives the reservoir, every example must be that event
+        let examples = app.get_result().examples.clone().unwrap();
+        let lines: Vec<usize> = examples
+            .by_level
+            .values()
+            .chain(examples.by_user.values())
+            .chain(examples.by_action.values())
+            .flatten()
+            .map(|example| example.line)
+            .collect();
+        assert_eq!(lines.len(), 3);
+        assert!(lines.iter().all(|&line| line == lines[0]));
+    }
+
+    #[test]
+    fn test_example_serialization() {
+        let example = Example {
+            line: 7,
+            event: Event::test("alice", "login", Level::Info),
+        };
+
+        let json = serde_json::to_string(&example).unwrap();
+        assert!(json.starts_with(r#"{"line":7,"ts":"2026-01-19T12:00:01Z","level":"INFO""#));
+        assert_eq!(serde_json::from_str::<Example>(&json).unwrap(), example);
+    }
+}
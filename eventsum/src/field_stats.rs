@@ -0,0 +1,165 @@
+use crate::event::Event;
+use crate::stats::{self, NameCount};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+
+/// Number of most frequent values reported per field
+const TOP_VALUES: usize = 10;
+
+/// Statistics over the numeric values of a field
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct NumericStats {
+    pub count: usize,
+    pub min: f64,
+    pub p50: f64,
+    pub p95: f64,
+    pub max: f64,
+    pub mean: f64,
+}
+
+impl NumericStats {
+    /// Computes the stats of the given values. None if empty
+    fn from_values(mut values: Vec<f64>) -> Option<Self> {
+        if values.is_empty() {
+            return None;
+        }
+
+        values.sort_unstable_by(f64::total_cmp);
+        Some(NumericStats {
+            count: values.len(),
+            min: values[0],
+            p50: stats::nearest_rank(&values, 50),
+            p95: stats::nearest_rank(&values, 95),
+            max: values[values.len() - 1],
+            mean: values.iter().sum::<f64>() / values.len() as f64,
+        })
+    }
+}
+
+/// Value counts and numeric stats of one extra field
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct FieldStats {
+    /// Events that have the field
+    pub present: usize,
+    /// Events without the field
+    pub missing: usize,
+    /// Number of distinct values
+    pub distinct: usize,
+    /// Most frequent values, by count then value
+    pub top_values: Vec<NameCount>,
+    /// Stats over numbers and numeric strings, absent if no value is numeric
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub numeric: Option<NumericStats>,
+}
+
+/// Returns the value as a string key, strings without quotes
+fn value_key(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+/// Returns the value as a number, numeric strings included
+fn value_number(value: &Value) -> Option<f64> {
+    match value {
+        Value::Number(n) => n.as_f64(),
+        Value::String(s) => s.trim().parse::<f64>().ok().filter(|n| n.is_finite()),
+        _ => None,
+    }
+}
+
+impl FieldStats {
+    /// Computes the stats of each extra field over the events
+    pub fn from_events(events: &[Event], fields: &[String]) -> BTreeMap<String, FieldStats> {
+        fields
+            .iter()
+            .map(|field| (field.clone(), Self::for_field(events, field)))
+            .collect()
+    }
+
+    fn for_field(events: &[Event], field: &str) -> FieldStats {
+        let mut counts: HashMap<String, usize> = HashMap::new();
+        let mut numbers = Vec::new();
+        let mut present = 0;
+
+        for value in events.iter().filter_map(|event| event.extra.get(field)) {
+            present += 1;
+            *counts.entry(value_key(value)).or_insert(0) += 1;
+            if let Some(number) = value_number(value) {
+                numbers.push(number);
+            }
+        }
+
+        FieldStats {
+            present,
+            missing: events.len() - present,
+            distinct: counts.len(),
+            top_values: stats::top_counts(&counts, TOP_VALUES),
+            numeric: NumericStats::from_values(numbers),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::event::Level;
+
+    fn event(extra: &[(&str, Value)]) -> Event {
+        Event {
+            extra: extra.iter().map(|(k, v)| (k.to_string(), v.clone())).collect(),
+            ..Event::test("alice", "login", Level::Info)
+        }
+    }
+
+    #[test]
+    fn test_value_counts() {
+        let events = vec![
+            event(&[("region", "eu".into())]),
+            event(&[("region", "us".into())]),
+            event(&[("region", "eu".into())]),
+            event(&[]),
+        ];
+
+        let stats = FieldStats::from_events(&events, &["region".to_string()]);
+        let region = &stats["region"];
+
+        assert_eq!(region.present, 3);
+        assert_eq!(region.missing, 1);
+        assert_eq!(region.distinct, 2);
+        assert_eq!(region.top_values[0].name, "eu");
+        assert_eq!(region.top_values[0].count, 2);
+        assert!(region.numeric.is_none());
+    }
+
+    #[test]
+    fn test_numeric_stats() {
+        let events = vec![
+            event(&[("status_code", 200.into())]),
+            event(&[("status_code", "404".into())]),
+            event(&[("status_code", 500.into())]),
+            event(&[("status_code", "n/a".into())]),
+        ];
+
+        let stats = FieldStats::from_events(&events, &["status_code".to_string()]);
+        let numeric = stats["status_code"].numeric.clone().unwrap();
+
+        assert_eq!(stats["status_code"].distinct, 4);
+        assert_eq!(numeric.count, 3);
+        assert_eq!(numeric.min, 200.0);
+        assert_eq!(numeric.p50, 404.0);
+        assert_eq!(numeric.max, 500.0);
+        assert_eq!(numeric.mean, 368.0);
+    }
+
+    #[test]
+    fn test_unknown_field() {
+        let stats = FieldStats::from_events(&[event(&[])], &["host".to_string()]);
+
+        assert_eq!(stats["host"].present, 0);
+        assert_eq!(stats["host"].missing, 1);
+        assert!(stats["host"].top_values.is_empty());
+    }
+}
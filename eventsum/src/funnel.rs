@@ -0,0 +1,257 @@
+use crate::event::Event;
+use crate::stats;
+use crate::timeline::{Timeline, Timelines};
+use chrono::{DateTime, TimeDelta, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+/// Action -> next action -> count
+pub type TransitionMatrix = BTreeMap<String, BTreeMap<String, usize>>;
+
+/// A single funnel step with its conversion
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FunnelStep {
+    /// Action name of the step
+    pub action: String,
+    /// Number of users that reached this step in order
+    pub users: usize,
+    /// users / users of the previous step (1.0 for the first step)
+    pub conversion_from_previous: f64,
+    /// users / users of the first step
+    pub conversion_from_start: f64,
+    /// Median time since the previous step. None for the first step or if nobody reached it
+    pub median_ms_from_previous: Option<u64>,
+}
+
+/// Funnel report over ordered action steps
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FunnelSummary {
+    /// Maximum time from the first to the last step, unbounded if None
+    pub window_ms: Option<u64>,
+    /// Number of distinct users considered
+    pub users: usize,
+    /// Valid events skipped because their ts could not be parsed
+    pub unparsed_timestamps: usize,
+    /// Steps in the requested order
+    pub steps: Vec<FunnelStep>,
+    /// Action -> next action counts within each user's timeline
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub transitions: Option<TransitionMatrix>,
+}
+
+/// Returns the timestamps of the deepest in-order walk through the steps
+/// Single pass over the timeline. For each step it keeps the walk with the latest start
+/// that reached it, since a later start leaves the most room in the window
+fn walk_steps(timeline: &Timeline, steps: &[String], window: TimeDelta) -> Vec<DateTime<Utc>> {
+    let mut walks: Vec<Option<Vec<DateTime<Utc>>>> = vec![None; steps.len()];
+
+    for &(ts, action) in timeline.iter() {
+        // Later steps first, so one event never advances two steps
+        for k in (0..steps.len()).rev() {
+            if steps[k] != action {
+                continue;
+            }
+            if k == 0 {
+                walks[0] = Some(vec![ts]);
+                continue;
+            }
+            let Some(previous) = &walks[k - 1] else {
+                continue;
+            };
+            let deadline = previous[0].checked_add_signed(window).unwrap_or(DateTime::<Utc>::MAX_UTC);
+            let later_start = walks[k].as_ref().is_none_or(|walk| walk[0] < previous[0]);
+            if ts <= deadline && later_start {
+                let mut walk = previous.clone();
+                walk.push(ts);
+                walks[k] = Some(walk);
+            }
+        }
+        if walks.last().is_some_and(|walk| walk.is_some()) {
+            break;
+        }
+    }
+
+    walks.into_iter().rev().flatten().next().unwrap_or_default()
+}
+
+impl FunnelSummary {
+    /// Computes how many users reached each step in order within the window
+    pub fn from_events(
+        events: &[Event],
+        steps: &[String],
+        window: Option<Duration>,
+        with_transitions: bool,
+    ) -> Self {
+        let timelines = Timelines::from_events(events);
+        let max_window = window
+            .and_then(|w| TimeDelta::from_std(w).ok())
+            .unwrap_or(TimeDelta::MAX);
+
+        let mut reached = vec![0usize; steps.len()];
+        let mut step_times: Vec<Vec<u64>> = vec![Vec::new(); steps.len()];
+
+        for timeline in timelines.by_user.values() {
+            let times = walk_steps(timeline, steps, max_window);
+            for (i, ts) in times.iter().enumerate() {
+                reached[i] += 1;
+                if i > 0 {
+                    let delta = (*ts - times[i - 1]).num_milliseconds();
+                    step_times[i].push(delta.max(0) as u64);
+                }
+            }
+        }
+
+        let first_users = reached.first().copied().unwrap_or(0);
+        let ratio = |num: usize, den: usize| if den == 0 { 0.0 } else { num as f64 / den as f64 };
+
+        let funnel_steps = steps
+            .iter()
+            .enumerate()
+            .map(|(i, action)| {
+                let mut times = std::mem::take(&mut step_times[i]);
+                times.sort_unstable();
+                FunnelStep {
+                    action: action.clone(),
+                    users: reached[i],
+                    conversion_from_previous: if i == 0 {
+                        1.0
+                    } else {
+                        ratio(reached[i], reached[i - 1])
+                    },
+                    conversion_from_start: ratio(reached[i], first_users),
+                    median_ms_from_previous: (!times.is_empty())
+                        .then(|| stats::nearest_rank(&times, 50)),
+                }
+            })
+            .collect();
+
+        let transitions = with_transitions.then(|| {
+            let mut matrix = TransitionMatrix::new();
+            for timeline in timelines.by_user.values() {
+                for pair in timeline.windows(2) {
+                    *matrix
+                        .entry(pair[0].1.to_string())
+                        .or_default()
+                        .entry(pair[1].1.to_string())
+                        .or_insert(0) += 1;
+                }
+            }
+            matrix
+        });
+
+        FunnelSummary {
+            window_ms: window.map(|w| w.as_millis().try_into().unwrap_or(u64::MAX)),
+            users: timelines.by_user.len(),
+            unparsed_timestamps: timelines.unparsed_timestamps,
+            steps: funnel_steps,
+            transitions,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::event::Level;
+
+    fn event(ts: &str, user: &str, action: &str) -> Event {
+        Event { ts: ts.to_string(), duration_ms: 10, ..Event::test(user, action, Level::Info) }
+    }
+
+    fn steps() -> Vec<String> {
+        vec!["login".to_string(), "run_script".to_string(), "logout".to_string()]
+    }
+
+    #[test]
+    fn test_funnel_conversion() {
+        let events = vec![
+            event("2026-01-19T12:00:00Z", "alice", "login"),
+            event("2026-01-19T12:00:10Z", "alice", "run_script"),
+            event("2026-01-19T12:00:30Z", "alice", "logout"),
+            event("2026-01-19T12:00:00Z", "bob", "login"),
+            event("2026-01-19T12:00:20Z", "bob", "run_script"),
+            event("2026-01-19T12:00:00Z", "carol", "login"),
+        ];
+
+        let summary = FunnelSummary::from_events(&events, &steps(), None, false);
+
+        assert_eq!(summary.steps[0].users, 3);
+        assert_eq!(summary.steps[1].users, 2);
+        assert_eq!(summary.steps[2].users, 1);
+        assert_eq!(summary.steps[2].conversion_from_previous, 0.5);
+        assert_eq!(summary.steps[0].median_ms_from_previous, None);
+        // alice 10s, bob 20s -> nearest-rank median is 10s
+        assert_eq!(summary.steps[1].median_ms_from_previous, Some(10_000));
+        assert!(summary.transitions.is_none());
+    }
+
+    #[test]
+    fn test_funnel_requires_order() {
+        let events = vec![
+            event("2026-01-19T12:00:00Z", "alice", "run_script"),
+            event("2026-01-19T12:00:10Z", "alice", "login"),
+        ];
+
+        let summary = FunnelSummary::from_events(&events, &steps(), None, false);
+
+        assert_eq!(summary.steps[0].users, 1);
+        assert_eq!(summary.steps[1].users, 0);
+        assert_eq!(summary.steps[1].median_ms_from_previous, None);
+    }
+
+    #[test]
+    fn test_funnel_window() {
+        let events = vec![
+            event("2026-01-19T12:00:00Z", "alice", "login"),
+            event("2026-01-19T13:00:00Z", "alice", "run_script"),
+            // Later retry that fits the window
+            event("2026-01-19T14:00:00Z", "alice", "login"),
+            event("2026-01-19T14:00:05Z", "alice", "run_script"),
+        ];
+
+        let summary = FunnelSummary::from_events(
+            &events,
+            &steps(),
+            Some(Duration::from_secs(60)),
+            false,
+        );
+
+        assert_eq!(summary.steps[1].users, 1);
+        assert_eq!(summary.steps[1].median_ms_from_previous, Some(5_000));
+    }
+
+    #[test]
+    fn test_funnel_repeated_step() {
+        let steps = vec!["login".to_string(), "login".to_string()];
+        let events = vec![
+            event("2026-01-19T12:00:00Z", "alice", "login"),
+            event("2026-01-19T12:00:00Z", "bob", "login"),
+            event("2026-01-19T12:00:03Z", "bob", "login"),
+        ];
+
+        let summary = FunnelSummary::from_events(&events, &steps, None, false);
+
+        assert_eq!(summary.steps[0].users, 2);
+        assert_eq!(summary.steps[1].users, 1);
+        assert_eq!(summary.steps[1].median_ms_from_previous, Some(3_000));
+    }
+
+    #[test]
+    fn test_funnel_transitions() {
+        let events = vec![
+            event("2026-01-19T12:00:00Z", "alice", "login"),
+            event("2026-01-19T12:00:01Z", "alice", "run_script"),
+            event("2026-01-19T12:00:00Z", "bob", "login"),
+            event("2026-01-19T12:00:01Z", "bob", "run_script"),
+            event("2026-01-19T12:00:02Z", "bob", "logout"),
+        ];
+
+        let summary = FunnelSummary::from_events(&events, &steps(), None, true);
+        let transitions = summary.transitions.unwrap();
+
+        assert_eq!(transitions["login"]["run_script"], 2);
+        assert_eq!(transitions["run_script"]["logout"], 1);
+        assert!(!transitions.contains_key("logout"));
+    }
+}
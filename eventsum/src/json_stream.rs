@@ -0,0 +1,245 @@
+use std::io::{self, BufRead};
+
+/// A single JSON value cut out of the stream, not yet parsed
+#[derive(Debug, Clone, PartialEq)]
+pub struct Record {
+    /// Raw bytes of the value
+    pub bytes: Vec<u8>,
+    /// 1-based line where the value starts
+    pub line: usize,
+    /// 0-based byte offset where the value starts
+    pub offset: u64,
+    /// Longer than the byte limit, bytes only holds its start
+    pub too_long: bool,
+}
+
+/// Splits a stream of JSON values into records without parsing them
+///
+/// Accepts concatenated values (`{..}{..}`, one per line or pretty-printed across lines)
+/// and top-level arrays (`[ {..}, {..} ]`), whose elements become records.
+/// Only brackets and strings are tracked and only one record is held in memory at a time.
+/// A record longer than max_bytes (an unbalanced `{` for instance) is cut off and the
+/// stream resyncs at the next line, so it cannot swallow the rest of the input.
+pub struct JsonRecords<R: BufRead> {
+    reader: R,
+    max_bytes: Option<usize>,
+    line: usize,
+    offset: u64,
+    /// Inside a top-level array whose elements are records
+    in_array: bool,
+    /// Byte read ahead while scanning a scalar
+    pending: Option<u8>,
+}
+
+impl<R: BufRead> JsonRecords<R> {
+    pub fn new(reader: R, max_bytes: Option<usize>) -> Self {
+        JsonRecords {
+            reader,
+            max_bytes,
+            line: 1,
+            offset: 0,
+            in_array: false,
+            pending: None,
+        }
+    }
+
+    /// Reads the next byte, tracking line and offset
+    fn next_byte(&mut self) -> io::Result<Option<u8>> {
+        if let Some(byte) = self.pending.take() {
+            return Ok(Some(byte));
+        }
+
+        let buf = self.reader.fill_buf()?;
+        let Some(&byte) = buf.first() else {
+            return Ok(None);
+        };
+        self.reader.consume(1);
+        self.offset += 1;
+        if byte == b'\n' {
+            self.line += 1;
+        }
+        Ok(Some(byte))
+    }
+
+    /// Returns true once bytes holds more than max_bytes
+    fn over_limit(&self, bytes: &[u8]) -> bool {
+        self.max_bytes.is_some_and(|max| bytes.len() > max)
+    }
+
+    /// Skips the rest of the current line and leaves any top-level array
+    fn resync(&mut self) -> io::Result<()> {
+        self.in_array = false;
+        while let Some(byte) = self.next_byte()? {
+            if byte == b'\n' {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    /// Reads the rest of a record starting with `first`
+    /// Returns its bytes and whether it was cut off at max_bytes
+    fn read_record(&mut self, first: u8) -> io::Result<(Vec<u8>, bool)> {
+        let mut bytes = vec![first];
+
+        // Scalars (and garbage) end at whitespace, a separator or a closing bracket
+        if first != b'{' && first != b'[' && first != b'"' {
+            while let Some(byte) = self.next_byte()? {
+                if byte.is_ascii_whitespace() || matches!(byte, b',' | b']' | b'{' | b'[') {
+                    self.pending = Some(byte);
+                    break;
+                }
+                bytes.push(byte);
+                if self.over_limit(&bytes) {
+                    self.resync()?;
+                    return Ok((bytes, true));
+                }
+            }
+            return Ok((bytes, false));
+        }
+
+        let mut depth: usize = if first == b'"' { 0 } else { 1 };
+        let mut in_string = first == b'"';
+        let mut escaped = false;
+
+        while let Some(byte) = self.next_byte()? {
+            bytes.push(byte);
+            if self.over_limit(&bytes) {
+                self.resync()?;
+                return Ok((bytes, true));
+            }
+            if in_string {
+                match byte {
+                    _ if escaped => escaped = false,
+                    b'\\' => escaped = true,
+                    b'"' => {
+                        in_string = false;
+                        if depth == 0 {
+                            break;
+                        }
+                    }
+                    _ => {}
+                }
+                continue;
+            }
+
+            match byte {
+                b'"' => in_string = true,
+                b'{' | b'[' => depth += 1,
+                b'}' | b']' => {
+                    depth = depth.saturating_sub(1);
+                    if depth == 0 {
+                        break;
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        // Truncated records at EOF are returned as is and fail to parse later
+        Ok((bytes, false))
+    }
+}
+
+impl<R: BufRead> Iterator for JsonRecords<R> {
+    type Item = io::Result<Record>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let byte = match self.next_byte() {
+                Ok(Some(byte)) => byte,
+                Ok(None) => return None,
+                Err(e) => return Some(Err(e)),
+            };
+
+            match byte {
+                b if b.is_ascii_whitespace() => continue,
+                // Skip a UTF-8 byte order mark at the start
+                0xEF | 0xBB | 0xBF if self.offset <= 3 => continue,
+                b'[' if !self.in_array => {
+                    self.in_array = true;
+                    continue;
+                }
+                b']' if self.in_array => {
+                    self.in_array = false;
+                    continue;
+                }
+                b',' if self.in_array => continue,
+                first => {
+                    let (line, offset) = (self.line, self.offset - 1);
+                    return Some(self.read_record(first).map(|(bytes, too_long)| Record {
+                        bytes,
+                        line,
+                        offset,
+                        too_long,
+                    }));
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn records(input: &str) -> Vec<Record> {
+        JsonRecords::new(input.as_bytes(), None)
+            .collect::<io::Result<Vec<_>>>()
+            .unwrap()
+    }
+
+    fn texts(input: &str) -> Vec<String> {
+        records(input)
+            .into_iter()
+            .map(|r| String::from_utf8(r.bytes).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_concatenated_values() {
+        let input = "{\"a\":1}{\"b\":\"}\"}\n{\n  \"c\": [1, {\"d\": 2}]\n}\n";
+
+        assert_eq!(texts(input), vec![r#"{"a":1}"#, r#"{"b":"}"}"#, "{\n  \"c\": [1, {\"d\": 2}]\n}"]);
+    }
+
+    #[test]
+    fn test_top_level_array() {
+        let input = "[\n  {\"a\": 1},\n  {\"b\": \"x\\\"]\"}\n]\n[{\"c\":3}]";
+
+        assert_eq!(texts(input), vec![r#"{"a": 1}"#, r#"{"b": "x\"]"}"#, r#"{"c":3}"#]);
+    }
+
+    #[test]
+    fn test_positions() {
+        let input = "{\"a\":1}\n\n  {\"b\":2}";
+        let records = records(input);
+
+        assert_eq!(records[0].line, 1);
+        assert_eq!(records[0].offset, 0);
+        assert_eq!(records[1].line, 3);
+        assert_eq!(records[1].offset, 11);
+    }
+
+    #[test]
+    fn test_garbage_and_truncated() {
+        let input = "not-json {\"a\":1} {\"b\":";
+
+        assert_eq!(texts(input), vec!["not-json", r#"{"a":1}"#, r#"{"b":"#]);
+    }
+
+    #[test]
+    fn test_unbalanced_record_is_cut_off() {
+        let input = "{\"a\":1}\n{\"b\": [1, 2, 3, 4, 5, 6, 7\n{\"c\":3}\n[{\"d\":4}, {\"e\": \"xxxxxxxxxxxxxxxx\"}]\n{\"f\":6}";
+        let records: Vec<Record> = JsonRecords::new(input.as_bytes(), Some(16))
+            .collect::<io::Result<Vec<_>>>()
+            .unwrap();
+
+        let too_long: Vec<bool> = records.iter().map(|r| r.too_long).collect();
+        assert_eq!(too_long, vec![false, true, false, false, true, false]);
+        assert_eq!(records[1].bytes.len(), 17);
+        assert_eq!(records[2].bytes, br#"{"c":3}"#);
+        assert_eq!(records[2].line, 3);
+        assert_eq!(records[5].bytes, br#"{"f":6}"#);
+    }
+}
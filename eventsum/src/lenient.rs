@@ -0,0 +1,256 @@
+use crate::event::{Event, Level};
+use crate::mapping::FieldMapping;
+use crate::severity::LevelScale;
+use crate::stats;
+use serde::{Deserialize, Serialize};
+use serde_json::{Number, Value};
+
+/// Counts of values coerced in lenient mode, one counter per coercion
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Coercions {
+    /// duration_ms given as a string, e.g. "120"
+    pub numeric_strings: usize,
+    /// Fractional duration_ms rounded to the nearest integer, halves away from zero
+    pub rounded_floats: usize,
+    /// Negative duration_ms clamped to 0
+    pub negative_clamped: usize,
+    /// Explicit null duration_ms read as 0
+    pub null_durations: usize,
+    /// Level not in upper case, e.g. "info"
+    pub level_case: usize,
+    /// Level with surrounding whitespace trimmed, e.g. " INFO"
+    pub level_whitespace: usize,
+    /// Level alias such as WARNING or ERR
+    pub level_aliases: usize,
+    /// Numeric user or action converted to a string
+    pub numbers_to_strings: usize,
+    /// True once a counter saturated, reported through the summary's overflowed flag
+    #[serde(skip)]
+    pub overflowed: bool,
+}
+
+impl Coercions {
+    /// Adds the counters of other, saturating at usize::MAX
+    fn add(&mut self, other: &Coercions) {
+        self.overflowed |= other.overflowed;
+        self.overflowed |= stats::add_count(&mut self.numeric_strings, other.numeric_strings);
+        self.overflowed |= stats::add_count(&mut self.rounded_floats, other.rounded_floats);
+        self.overflowed |= stats::add_count(&mut self.negative_clamped, other.negative_clamped);
+        self.overflowed |= stats::add_count(&mut self.null_durations, other.null_durations);
+        self.overflowed |= stats::add_count(&mut self.level_case, other.level_case);
+        self.overflowed |= stats::add_count(&mut self.level_whitespace, other.level_whitespace);
+        self.overflowed |= stats::add_count(&mut self.level_aliases, other.level_aliases);
+        self.overflowed |= stats::add_count(&mut self.numbers_to_strings, other.numbers_to_strings);
+    }
+}
+
+/// Coerces a JSON number to duration_ms: fractions are rounded, negatives clamped to 0
+fn number_to_duration(n: &Number, coercions: &mut Coercions) -> Result<u64, String> {
+    if let Some(duration) = n.as_u64() {
+        return Ok(duration);
+    }
+    if n.as_i64().is_some() {
+        coercions.overflowed |= stats::add_count(&mut coercions.negative_clamped, 1);
+        return Ok(0);
+    }
+
+    let float = n.as_f64().ok_or_else(|| format!("invalid duration_ms {}", n))?;
+    let rounded = float.round();
+    if rounded != float {
+        coercions.overflowed |= stats::add_count(&mut coercions.rounded_floats, 1);
+    }
+    if rounded < 0.0 {
+        coercions.overflowed |= stats::add_count(&mut coercions.negative_clamped, 1);
+        return Ok(0);
+    }
+    if rounded >= u64::MAX as f64 {
+        return Err(format!("duration_ms {} out of range", n));
+    }
+    Ok(rounded as u64)
+}
+
+fn duration(value: Option<&Value>, coercions: &mut Coercions) -> Result<u64, String> {
+    match value {
+        None => Err("missing field duration_ms".to_string()),
+        Some(Value::Null) => {
+            coercions.overflowed |= stats::add_count(&mut coercions.null_durations, 1);
+            Ok(0)
+        }
+        Some(Value::Number(n)) => number_to_duration(n, coercions),
+        Some(Value::String(s)) => {
+            let n: Number = s
+                .trim()
+                .parse()
+                .map_err(|_| format!("invalid duration_ms '{}'", s))?;
+            coercions.overflowed |= stats::add_count(&mut coercions.numeric_strings, 1);
+            number_to_duration(&n, coercions)
+        }
+        Some(other) => Err(format!("invalid duration_ms {}", other)),
+    }
+}
+
+fn level(value: Option<&Value>, scale: &LevelScale, coercions: &mut Coercions) -> Result<Level, String> {
+    let raw = match value {
+        Some(Value::String(s)) => s,
+        Some(other) => return Err(format!("invalid level {}", other)),
+        None => return Err("missing field level".to_string()),
+    };
+    let name = raw.trim();
+    if name != raw {
+        coercions.overflowed |= stats::add_count(&mut coercions.level_whitespace, 1);
+    }
+    let upper = name.to_ascii_uppercase();
+
+    if let Some(level) = Level::from_alias(&upper) {
+        coercions.overflowed |= stats::add_count(&mut coercions.level_aliases, 1);
+        return Ok(level);
+    }
+    let level = scale.parse_level(&upper).map_err(|_| format!("unknown level '{}'", name))?;
+    if upper != name {
+        coercions.overflowed |= stats::add_count(&mut coercions.level_case, 1);
+    }
+    Ok(level)
+}
+
+fn text(value: Option<&Value>, field: &str, coercions: &mut Coercions) -> Result<String, String> {
+    match value {
+        Some(Value::String(s)) => Ok(s.clone()),
+        Some(Value::Number(n)) if field != "ts" => {
+            coercions.overflowed |= stats::add_count(&mut coercions.numbers_to_strings, 1);
+            Ok(n.to_string())
+        }
+        Some(other) => Err(format!("field {} has unexpected value {}", field, other)),
+        None => Err(format!("missing field {}", field)),
+    }
+}
+
+/// Parses a JSON line into an Event, coercing loosely typed fields
+///
+/// - duration_ms: numeric strings are parsed, fractions rounded (halves away from zero),
+///   negatives clamped to 0, null read as 0. A missing duration_ms is an error
+/// - level: case-insensitive and trimmed, WARNING, ERR, CRIT and EMERG are accepted as aliases
+/// - user, action: numbers are converted to strings
+///
+/// Coercions are added to coercions only if the whole line parses
+pub fn parse_line(
+    line: &str,
+    mapping: &FieldMapping,
+    scale: &LevelScale,
+    coercions: &mut Coercions,
+) -> Result<Event, String> {
+    let root: Value = serde_json::from_str(line).map_err(|e| format!("invalid JSON: {}", e))?;
+    let mut applied = Coercions::default();
+
+    let event = Event {
+        ts: text(mapping.lookup(&root, "ts"), "ts", &mut applied)?,
+        level: level(mapping.lookup(&root, "level"), scale, &mut applied)?,
+        user: text(mapping.lookup(&root, "user"), "user", &mut applied)?,
+        action: text(mapping.lookup(&root, "action"), "action", &mut applied)?,
+        duration_ms: duration(mapping.lookup(&root, "duration_ms"), &mut applied)?,
+        extra: mapping.extra_fields(&root),
+    };
+
+    coercions.add(&applied);
+    Ok(event)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(line: &str) -> (Result<Event, String>, Coercions) {
+        let mut coercions = Coercions::default();
+        let event = parse_line(line, &FieldMapping::default(), &LevelScale::default(), &mut coercions);
+        (event, coercions)
+    }
+
+    fn line(level: &str, duration: &str) -> String {
+        format!(
+            r#"{{"ts":"2026-01-19T12:00:01Z","level":{},"user":"alice","action":"login","duration_ms":{}}}"#,
+            level, duration
+        )
+    }
+
+    #[test]
+    fn test_strict_values_are_not_coerced() {
+        let (event, coercions) = parse(&line(r#""INFO""#, "120"));
+
+        assert_eq!(event.unwrap().duration_ms, 120);
+        assert_eq!(coercions, Coercions::default());
+    }
+
+    #[test]
+    fn test_duration_coercions() {
+        let cases = [
+            (r#""120""#, 120, "numeric_strings"),
+            ("120.7", 121, "rounded_floats"),
+            ("120.5", 121, "rounded_floats"),
+            ("-1", 0, "negative_clamped"),
+            ("null", 0, "null_durations"),
+        ];
+
+        for (raw, expected, counter) in cases {
+            let (event, coercions) = parse(&line(r#""INFO""#, raw));
+
+            assert_eq!(event.unwrap().duration_ms, expected, "{}", raw);
+            let counts = serde_json::to_value(&coercions).unwrap();
+            assert_eq!(counts[counter], 1, "{}", raw);
+        }
+    }
+
+    #[test]
+    fn test_numeric_string_with_fraction() {
+        let (event, coercions) = parse(&line(r#""INFO""#, r#""-3.2""#));
+
+        assert_eq!(event.unwrap().duration_ms, 0);
+        assert_eq!(coercions.numeric_strings, 1);
+        assert_eq!(coercions.rounded_floats, 1);
+        assert_eq!(coercions.negative_clamped, 1);
+    }
+
+    #[test]
+    fn test_level_coercions() {
+        let (event, coercions) = parse(&line(r#""warning""#, "1"));
+        assert_eq!(event.unwrap().level, Level::Warn);
+        assert_eq!(coercions.level_aliases, 1);
+
+        let (event, coercions) = parse(&line(r#""Error""#, "1"));
+        assert_eq!(event.unwrap().level, Level::Error);
+        assert_eq!(coercions.level_case, 1);
+
+        let (event, coercions) = parse(&line(r#"" INFO ""#, "1"));
+        assert_eq!(event.unwrap().level, Level::Info);
+        assert_eq!(coercions.level_whitespace, 1);
+        assert_eq!(coercions.level_case, 0);
+
+        assert!(parse(&line(r#""verbose""#, "1")).0.is_err());
+    }
+
+    #[test]
+    fn test_numeric_user() {
+        let (event, coercions) =
+            parse(r#"{"ts":"2026-01-19T12:00:01Z","level":"INFO","user":42,"action":"login","duration_ms":1}"#);
+
+        assert_eq!(event.unwrap().user, "42");
+        assert_eq!(coercions.numbers_to_strings, 1);
+    }
+
+    #[test]
+    fn test_failed_line_adds_no_coercions() {
+        let (event, coercions) = parse(r#"{"ts":"2026-01-19T12:00:01Z","level":"info","duration_ms":"5"}"#);
+
+        assert!(event.is_err());
+        assert_eq!(coercions, Coercions::default());
+        assert!(parse(&line(r#""INFO""#, r#""abc""#)).0.is_err());
+        assert!(parse(&line(r#""INFO""#, "true")).0.is_err());
+    }
+
+    #[test]
+    fn test_missing_duration_is_not_coerced() {
+        let (event, coercions) =
+            parse(r#"{"ts":"2026-01-19T12:00:01Z","level":"INFO","user":"alice","action":"login"}"#);
+
+        assert_eq!(event.unwrap_err(), "missing field duration_ms");
+        assert_eq!(coercions, Coercions::default());
+    }
+}
@@ -0,0 +1,196 @@
+use std::io::{self, BufRead};
+
+/// UTF-8 byte order mark, skipped at the start of the input
+pub const BOM: &[u8] = b"\xEF\xBB\xBF";
+
+/// Default --max-line-bytes, 16 MiB
+pub const DEFAULT_MAX_LINE_BYTES: usize = 16 * 1024 * 1024;
+
+/// Longest lossy preview of an invalid line kept for error messages, in bytes
+const PREVIEW_BYTES: usize = 80;
+
+/// A line read from the input, without its line ending
+#[derive(Debug, Clone, PartialEq)]
+pub enum Line {
+    /// Valid UTF-8 text
+    Text(String),
+    /// Not valid UTF-8, with a lossy preview of its start
+    InvalidUtf8 { preview: String },
+    /// Longer than the byte limit. Only the byte count is kept
+    TooLong { bytes: usize },
+}
+
+impl Line {
+    /// Returns the reason a line cannot be parsed, None for text
+    pub fn bad_reason(&self) -> Option<String> {
+        match self {
+            Line::Text(_) => None,
+            Line::InvalidUtf8 { preview } => Some(format!("invalid UTF-8: {}", preview)),
+            Line::TooLong { bytes } => Some(format!("line of {} bytes exceeds --max-line-bytes", bytes)),
+        }
+    }
+}
+
+/// Splits input into lines like `BufRead::lines` without failing on invalid UTF-8
+///
+/// Lines end with `\n` or `\r\n` and a leading BOM is skipped.
+/// Lines longer than max_bytes are read through but not buffered, so a huge
+/// unterminated line costs at most max_bytes of memory.
+pub struct Lines<R: BufRead> {
+    reader: R,
+    max_bytes: Option<usize>,
+    at_start: bool,
+}
+
+impl<R: BufRead> Lines<R> {
+    pub fn new(reader: R, max_bytes: Option<usize>) -> Self {
+        Lines {
+            reader,
+            max_bytes,
+            at_start: true,
+        }
+    }
+
+    /// Skips a BOM at the start of the input
+    fn skip_bom(&mut self) -> io::Result<()> {
+        self.at_start = false;
+        let buf = self.reader.fill_buf()?;
+        if buf.starts_with(BOM) {
+            self.reader.consume(BOM.len());
+        }
+        Ok(())
+    }
+
+    /// Reads the next line into bytes, up to max_bytes
+    /// Returns the full length of the line, None at the end of the input
+    fn read_line(&mut self, bytes: &mut Vec<u8>) -> io::Result<Option<usize>> {
+        let mut length: usize = 0;
+        let mut read_any = false;
+        let mut last = None;
+
+        loop {
+            let buf = self.reader.fill_buf()?;
+            if buf.is_empty() {
+                break;
+            }
+            read_any = true;
+
+            let (chunk, done) = match buf.iter().position(|&b| b == b'\n') {
+                Some(end) => (&buf[..end], true),
+                None => (buf, false),
+            };
+            let room = self.max_bytes.map_or(chunk.len(), |max| (max + 1).saturating_sub(bytes.len()));
+            bytes.extend_from_slice(&chunk[..chunk.len().min(room)]);
+            length = length.saturating_add(chunk.len());
+            last = chunk.last().copied().or(last);
+
+            let used = chunk.len() + usize::from(done);
+            self.reader.consume(used);
+            if done {
+                break;
+            }
+        }
+
+        if !read_any {
+            return Ok(None);
+        }
+        if last == Some(b'\r') {
+            length -= 1;
+            bytes.truncate(length);
+        }
+        Ok(Some(length))
+    }
+}
+
+impl<R: BufRead> Iterator for Lines<R> {
+    type Item = io::Result<Line>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.at_start
+            && let Err(e) = self.skip_bom()
+        {
+            return Some(Err(e));
+        }
+
+        let mut bytes = Vec::new();
+        let length = match self.read_line(&mut bytes) {
+            Ok(Some(length)) => length,
+            Ok(None) => return None,
+            Err(e) => return Some(Err(e)),
+        };
+
+        if self.max_bytes.is_some_and(|max| length > max) {
+            return Some(Ok(Line::TooLong { bytes: length }));
+        }
+        Some(Ok(match String::from_utf8(bytes) {
+            Ok(text) => Line::Text(text),
+            Err(e) => {
+                let bytes = e.as_bytes();
+                let preview = String::from_utf8_lossy(&bytes[..bytes.len().min(PREVIEW_BYTES)]);
+                Line::InvalidUtf8 {
+                    preview: preview.into_owned(),
+                }
+            }
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines(input: &[u8], max_bytes: Option<usize>) -> Vec<Line> {
+        Lines::new(input, max_bytes).map(|line| line.unwrap()).collect()
+    }
+
+    fn text(s: &str) -> Line {
+        Line::Text(s.to_string())
+    }
+
+    #[test]
+    fn test_line_endings() {
+        assert_eq!(lines(b"a\nb\r\n\nc", None), vec![text("a"), text("b"), text(""), text("c")]);
+        assert_eq!(lines(b"a\r\n", None), vec![text("a")]);
+        assert_eq!(lines(b"a\rb\n", None), vec![text("a\rb")]);
+        assert!(lines(b"", None).is_empty());
+    }
+
+    #[test]
+    fn test_bom_is_skipped() {
+        assert_eq!(lines(b"\xEF\xBB\xBFa\r\nb", None), vec![text("a"), text("b")]);
+        assert_eq!(lines(b"\xEF\xBB\xBF", None), vec![]);
+    }
+
+    #[test]
+    fn test_invalid_utf8() {
+        let lines = lines(b"ok\nbad \xFF line\nok\n", None);
+
+        assert_eq!(lines[0], text("ok"));
+        assert_eq!(
+            lines[1],
+            Line::InvalidUtf8 {
+                preview: "bad \u{FFFD} line".to_string()
+            }
+        );
+        assert_eq!(lines[2], text("ok"));
+        assert!(lines[1].bad_reason().unwrap().starts_with("invalid UTF-8: "));
+    }
+
+    #[test]
+    fn test_max_line_bytes() {
+        let long = "x".repeat(100);
+        let input = format!("short\n{}\r\n12345\n", long);
+        // A small buffer makes the long line span several reads
+        let reader = io::BufReader::with_capacity(8, input.as_bytes());
+
+        let lines: Vec<Line> = Lines::new(reader, Some(5)).map(|line| line.unwrap()).collect();
+
+        assert_eq!(lines, vec![text("short"), Line::TooLong { bytes: 100 }, text("12345")]);
+    }
+
+    #[test]
+    fn test_unterminated_long_line() {
+        assert_eq!(lines(b"123456", Some(5)), vec![Line::TooLong { bytes: 6 }]);
+        assert_eq!(lines(b"12345\r\n", Some(5)), vec![text("12345")]);
+    }
+}
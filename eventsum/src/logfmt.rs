@@ -0,0 +1,108 @@
+/// Splits a logfmt line into key/value pairs
+/// Values may be bare (`key=value`), quoted (`key="a b"`) with \" \\ \n \t escapes,
+/// or missing (`key` or `key=`), which yields an empty value.
+/// Returns an error for unterminated quotes or empty keys
+pub fn parse_pairs(line: &str) -> Result<Vec<(String, String)>, String> {
+    let mut pairs = Vec::new();
+    let mut chars = line.chars().peekable();
+
+    loop {
+        // Skip whitespace between pairs
+        while chars.peek().is_some_and(|c| c.is_whitespace()) {
+            chars.next();
+        }
+        if chars.peek().is_none() {
+            break;
+        }
+
+        let mut key = String::new();
+        while let Some(&c) = chars.peek() {
+            if c == '=' || c.is_whitespace() {
+                break;
+            }
+            key.push(c);
+            chars.next();
+        }
+        if key.is_empty() {
+            return Err("empty key".to_string());
+        }
+
+        let mut value = String::new();
+        if chars.peek() == Some(&'=') {
+            chars.next();
+            if chars.peek() == Some(&'"') {
+                chars.next();
+                let mut closed = false;
+                while let Some(c) = chars.next() {
+                    match c {
+                        '"' => {
+                            closed = true;
+                            break;
+                        }
+                        '\\' => match chars.next() {
+                            Some('n') => value.push('\n'),
+                            Some('t') => value.push('\t'),
+                            Some('r') => value.push('\r'),
+                            Some(other) => value.push(other),
+                            None => break,
+                        },
+                        other => value.push(other),
+                    }
+                }
+                if !closed {
+                    return Err(format!("unterminated quoted value for key '{}'", key));
+                }
+            } else {
+                while let Some(&c) = chars.peek() {
+                    if c.is_whitespace() {
+                        break;
+                    }
+                    value.push(c);
+                    chars.next();
+                }
+            }
+        }
+
+        pairs.push((key, value));
+    }
+
+    Ok(pairs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_bare_pairs() {
+        let pairs = parse_pairs("ts=2026-01-19T12:00:01Z level=info user=alice").unwrap();
+
+        assert_eq!(pairs.len(), 3);
+        assert_eq!(pairs[0], ("ts".to_string(), "2026-01-19T12:00:01Z".to_string()));
+        assert_eq!(pairs[2], ("user".to_string(), "alice".to_string()));
+    }
+
+    #[test]
+    fn test_parse_quoted_and_escaped() {
+        let pairs = parse_pairs(r#"action="run \"big\" script" path="C:\\tmp" msg="a\nb""#).unwrap();
+
+        assert_eq!(pairs[0].1, r#"run "big" script"#);
+        assert_eq!(pairs[1].1, r"C:\tmp");
+        assert_eq!(pairs[2].1, "a\nb");
+    }
+
+    #[test]
+    fn test_parse_empty_and_flag_values() {
+        let pairs = parse_pairs("debug user= action=login").unwrap();
+
+        assert_eq!(pairs[0], ("debug".to_string(), String::new()));
+        assert_eq!(pairs[1], ("user".to_string(), String::new()));
+        assert_eq!(pairs[2].1, "login");
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(parse_pairs(r#"action="unterminated"#).is_err());
+        assert!(parse_pairs("=value").is_err());
+    }
+}
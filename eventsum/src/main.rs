@@ -1,24 +1,116 @@
 use clap::Parser;
-use log::{debug, error,info};
+use log::{debug, error, log_enabled, Level};
+use serde::Serialize;
+use std::io::{self, Write};
 use std::process;
 
 // Internal modules
+mod access_log;
+mod apdex;
 mod app;
+mod binary;
+mod diff;
 mod event;
+mod examples;
+mod field_stats;
+mod funnel;
+mod json_stream;
+mod lenient;
+mod lines;
+mod logfmt;
+mod mapping;
+mod otlp;
+mod parser;
+mod pattern;
 mod cli;
+mod quality;
 mod result;
+mod rules;
+mod sampling;
+mod schema;
+mod sessions;
+mod severity;
+mod stats;
+mod syslog;
+mod timeline;
 
-// TODO; when reading from std in, need reset at some point. Otherwise overflow
+use binary::BinaryFormat;
+use cli::{Command, OutputFormat};
+
+/// Serializes the report in the output format and writes it to stdout
+/// --pretty only applies to JSON. Exits with code 1 if serialization fails
+fn print_output<T: Serialize>(value: &T, cli: &cli::Cli) {
+    let bytes = match cli.output_format {
+        OutputFormat::Json if cli.pretty => serde_json::to_vec_pretty(value).map_err(|e| e.to_string()),
+        OutputFormat::Json => serde_json::to_vec(value).map_err(|e| e.to_string()),
+        OutputFormat::Msgpack => BinaryFormat::MessagePack.encode(value),
+        OutputFormat::Cbor => BinaryFormat::Cbor.encode(value),
+    };
+
+    let mut bytes = bytes.unwrap_or_else(|e| {
+        error!("Failed to serialize result: {}", e);
+        process::exit(1);
+    });
+    if cli.output_format == OutputFormat::Json {
+        bytes.push(b'\n');
+    }
+
+    let mut stdout = io::stdout().lock();
+    if let Err(e) = stdout.write_all(&bytes).and_then(|_| stdout.flush()) {
+        error!("Failed to write result: {}", e);
+        process::exit(1);
+    }
+}
+
+/// Builds the processing options
+/// Exits with code 2 if a referenced config file cannot be read
+fn options_or_exit(cli: &cli::Cli) -> app::Options {
+    cli.options().unwrap_or_else(|e| {
+        error!("Error reading options: {}", e);
+        process::exit(2);
+    })
+}
+
+/// Compares baseline and candidate and exits
+/// Exit code 2 if an input cannot be read, 3 if a regression gate failed
+fn run_diff(args: &cli::DiffArgs, cli: &cli::Cli, options: &app::Options) -> ! {
+    let load = |path: &str| {
+        diff::load_summary(path, options).unwrap_or_else(|e| {
+            error!("Error reading input {}: {}", path, e);
+            process::exit(2);
+        })
+    };
+    let baseline = load(&args.baseline);
+    let candidate = load(&args.candidate);
+
+    let report = diff::DiffReport::compare(&baseline, &candidate, &args.thresholds(), &options.level_scale);
+    print_output(&report, cli);
+
+    if !report.passed {
+        for regression in &report.regressions {
+            error!("Regression in {}: {}", regression.metric, regression.message);
+        }
+        process::exit(3);
+    }
+    process::exit(0);
+}
 
 fn main() {
     // Initialize logger (set RUST_LOG=debug for detailed output)
     env_logger::init();
     
     let cli = crate::cli::Cli::parse();
-    let mut app = app::App::new();
+    let options = options_or_exit(&cli);
     
     debug!("Starting eventsum with pretty={}", cli.pretty);
 
+    // Diff mode reads its own inputs
+    if let Some(Command::Diff(ref args)) = cli.command {
+        run_diff(args, &cli, &options);
+    }
+
+    let mut app = app::App::new(options);
+
     let result = match cli.input {
         Some(ref path) => {
             debug!("Reading from file: {}", path);
@@ -36,22 +128,37 @@ fn main() {
         process::exit(2);
     }
 
-    // Finalize: compute top users, p95, and outlier
-    app.finalize();
-    
-    // Generate and output JSON
-    match app.get_result().to_json(cli.pretty) {
-        Ok(json) => {
-            info!("Pretty Result:{}", json);
+    // Strict mode stops at the first bad line without a summary (exit code 4)
+    if let Some(bad) = app.strict_failure() {
+        error!("Strict mode: bad line {}: {}", bad.line, bad.reason);
+        process::exit(4);
+    }
+
+    match cli.command {
+        Some(Command::Sessions(ref args)) => {
+            print_output(&app.session_summary(args.session_gap), &cli);
+        }
+        Some(Command::Funnel(ref args)) => {
+            let summary = app.funnel_summary(&args.steps, args.window, args.transitions);
+            print_output(&summary, &cli);
         }
-        Err(e) => {
-            error!("Failed to serialize result to JSON: {}", e);
-            process::exit(1);
+        Some(Command::Diff(_)) => unreachable!("diff mode is handled before reading input"),
+        None => {
+            // Finalize: compute top users, p95, and outlier
+            app.finalize();
+            if log_enabled!(Level::Debug) && let Ok(json) = app.get_result().to_json(true) {
+                debug!("Pretty Result:{}", json);
+            }
+            print_output(app.get_result(), &cli);
         }
     }
+
+    // The summary is printed, but too many bad lines fail the run (exit code 5)
+    if let Some(message) = app.bad_line_limit_exceeded() {
+        error!("Bad line limit exceeded: {}", message);
+        process::exit(5);
+    }
     
     // Exit with success
     process::exit(0);
 }
-
-
@@ -0,0 +1,230 @@
+use crate::event::{self, Event};
+use crate::severity::LevelScale;
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::str::FromStr;
+
+/// Event fields that can be mapped
+const FIELDS: [&str; 5] = ["ts", "level", "user", "action", "duration_ms"];
+
+/// Maps Event fields to (possibly nested) JSON paths such as `ctx.user.id`
+/// Unmapped fields use their own name as path
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FieldMapping {
+    paths: HashMap<String, Vec<String>>,
+}
+
+/// A single `field=path` mapping from the command line
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldPath {
+    pub field: String,
+    pub path: String,
+}
+
+impl FromStr for FieldPath {
+    type Err = String;
+
+    /// Parses `field=path`, e.g. `user=ctx.user.id`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (field, path) = s
+            .split_once('=')
+            .ok_or_else(|| format!("expected FIELD=PATH, got '{}'", s))?;
+        if !FIELDS.contains(&field) {
+            return Err(format!("unknown field '{}', expected one of {}", field, FIELDS.join(", ")));
+        }
+        if path.is_empty() || path.split('.').any(str::is_empty) {
+            return Err(format!("invalid path '{}'", path));
+        }
+
+        Ok(FieldPath {
+            field: field.to_string(),
+            path: path.to_string(),
+        })
+    }
+}
+
+impl FieldMapping {
+    /// Adds a mapping, replacing an earlier one for the same field
+    pub fn insert(&mut self, mapping: FieldPath) {
+        let path = mapping.path.split('.').map(str::to_string).collect();
+        self.paths.insert(mapping.field, path);
+    }
+
+    /// Loads a TOML file with one `field = "path"` entry per mapped field
+    pub fn from_toml_file(path: &str) -> Result<Self, String> {
+        let content = fs::read_to_string(path).map_err(|e| format!("cannot read {}: {}", path, e))?;
+        let table: HashMap<String, String> =
+            toml::from_str(&content).map_err(|e| format!("invalid mapping file {}: {}", path, e))?;
+
+        let mut mapping = FieldMapping::default();
+        for (field, json_path) in table {
+            mapping.insert(format!("{}={}", field, json_path).parse()?);
+        }
+        Ok(mapping)
+    }
+
+    /// Returns the value at the mapped path of a field
+    pub fn lookup<'a>(&self, root: &'a Value, field: &str) -> Option<&'a Value> {
+        match self.paths.get(field) {
+            Some(path) => path.iter().try_fold(root, |value, key| value.get(key)),
+            None => root.get(field),
+        }
+    }
+
+    /// Returns the top-level keys not used by any field
+    /// The first segment of a nested path counts as used, e.g. `ctx` for `ctx.user.id`
+    pub fn extra_fields(&self, root: &Value) -> BTreeMap<String, Value> {
+        let Some(object) = root.as_object() else {
+            return BTreeMap::new();
+        };
+        let used: Vec<&str> = FIELDS
+            .iter()
+            .map(|field| match self.paths.get(*field) {
+                Some(path) => path[0].as_str(),
+                None => field,
+            })
+            .collect();
+
+        object
+            .iter()
+            .filter(|(key, _)| !used.contains(&key.as_str()))
+            .map(|(key, value)| (key.clone(), value.clone()))
+            .collect()
+    }
+
+    /// Returns the mapped value, which must be a string. Use --lenient to accept numbers
+    fn string_field(&self, root: &Value, field: &str) -> Result<String, String> {
+        match self.lookup(root, field) {
+            Some(Value::String(s)) => Ok(s.clone()),
+            Some(other) => Err(format!("field {} has unexpected value {}", field, other)),
+            None => Err(format!("missing field {}", field)),
+        }
+    }
+
+    /// Parses a JSON line into an Event using the mapping
+    /// Values are as strict as unmapped JSON lines, e.g. the level must be upper case
+    /// Returns the reason if the line is not JSON or a mapped field is missing or malformed
+    pub fn parse_line(&self, line: &str, scale: &LevelScale) -> Result<Event, String> {
+        let root: Value = serde_json::from_str(line).map_err(|e| format!("invalid JSON: {}", e))?;
+
+        let level = event::strict_level(&self.string_field(&root, "level")?, scale)?;
+        let duration_ms = match self.lookup(&root, "duration_ms") {
+            Some(value) => value
+                .as_u64()
+                .ok_or_else(|| format!("field duration_ms has unexpected value {}", value))?,
+            None => return Err("missing field duration_ms".to_string()),
+        };
+
+        Ok(Event {
+            ts: self.string_field(&root, "ts")?,
+            level,
+            user: self.string_field(&root, "user")?,
+            action: self.string_field(&root, "action")?,
+            duration_ms,
+            extra: self.extra_fields(&root),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::event::Level;
+
+    fn mapping(pairs: &[&str]) -> FieldMapping {
+        let mut mapping = FieldMapping::default();
+        for pair in pairs {
+            mapping.insert(pair.parse().unwrap());
+        }
+        mapping
+    }
+
+    #[test]
+    fn test_mapped_and_nested_fields() {
+        let mapping = mapping(&[
+            "ts=timestamp",
+            "level=severity",
+            "user=ctx.user.id",
+            "action=event",
+            "duration_ms=latency",
+        ]);
+        let line = r#"{"timestamp":"2026-01-19T12:00:01Z","severity":"WARN","ctx":{"user":{"id":"7"}},"event":"login","latency":120}"#;
+
+        let event = mapping.parse_line(line, &LevelScale::default()).unwrap();
+
+        assert_eq!(event.ts, "2026-01-19T12:00:01Z");
+        assert_eq!(event.level, Level::Warn);
+        assert_eq!(event.user, "7");
+        assert_eq!(event.action, "login");
+        assert_eq!(event.duration_ms, 120);
+    }
+
+    #[test]
+    fn test_extra_fields_skip_mapped_keys() {
+        let mapping = mapping(&["user=ctx.user.id", "duration_ms=latency"]);
+        let line = r#"{"ts":"t","level":"INFO","ctx":{"user":{"id":"a"}},"action":"x","latency":1,"region":"eu"}"#;
+
+        let event = mapping.parse_line(line, &LevelScale::default()).unwrap();
+
+        assert_eq!(event.extra.len(), 1);
+        assert_eq!(event.extra["region"], "eu");
+    }
+
+    #[test]
+    fn test_unmapped_fields_use_own_name() {
+        let mapping = mapping(&["user=userId"]);
+        let line = r#"{"ts":"2026-01-19T12:00:01Z","level":"INFO","userId":"alice","action":"login","duration_ms":1}"#;
+
+        let event = mapping.parse_line(line, &LevelScale::default()).unwrap();
+
+        assert_eq!(event.user, "alice");
+    }
+
+    #[test]
+    fn test_mapped_values_are_strict() {
+        let mapping = mapping(&["action=action"]);
+        let numeric_user = r#"{"ts":"2026-01-19T12:00:01Z","level":"INFO","user":42,"action":"login","duration_ms":1}"#;
+        let lower_level = numeric_user.replace(r#""INFO","user":42"#, r#""info","user":"alice""#);
+
+        assert_eq!(
+            mapping.parse_line(numeric_user, &LevelScale::default()).unwrap_err(),
+            "field user has unexpected value 42"
+        );
+        assert_eq!(
+            mapping.parse_line(&lower_level, &LevelScale::default()).unwrap_err(),
+            "unknown level 'info'"
+        );
+    }
+
+    #[test]
+    fn test_missing_nested_path() {
+        let mapping = mapping(&["user=ctx.user.id"]);
+        let line = r#"{"ts":"2026-01-19T12:00:01Z","level":"INFO","ctx":{},"action":"login","duration_ms":1}"#;
+
+        let err = mapping.parse_line(line, &LevelScale::default()).unwrap_err();
+
+        assert_eq!(err, "missing field user");
+    }
+
+    #[test]
+    fn test_invalid_field_paths() {
+        assert!("nope=x".parse::<FieldPath>().is_err());
+        assert!("user".parse::<FieldPath>().is_err());
+        assert!("user=a..b".parse::<FieldPath>().is_err());
+    }
+
+    #[test]
+    fn test_toml_file() {
+        let path = "/tmp/eventsum_mapping_test.toml";
+        fs::write(path, "user = \"ctx.user\"\naction = \"event\"\n").unwrap();
+
+        let mapping = FieldMapping::from_toml_file(path).unwrap();
+        std::fs::remove_file(path).ok();
+
+        let line = r#"{"ts":"t","level":"INFO","ctx":{"user":"bob"},"event":"x","duration_ms":1}"#;
+        let event = mapping.parse_line(line, &LevelScale::default()).unwrap();
+        assert_eq!(event.user, "bob");
+        assert_eq!(event.action, "x");
+    }
+}
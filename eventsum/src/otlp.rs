@@ -0,0 +1,406 @@
+use crate::event::{Event, Level};
+use crate::severity::LevelScale;
+use chrono::DateTime;
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+/// Attribute names used to map OTLP records onto Event fields
+#[derive(Debug, Clone)]
+pub struct OtlpOptions {
+    /// Attribute holding the user, falls back to the service.name resource attribute
+    pub user_attribute: String,
+    /// Attribute holding the action, falls back to the span name or the log body
+    pub action_attribute: String,
+    /// Attribute holding duration_ms of log records, 0 if absent. Spans use end - start
+    pub duration_attribute: String,
+}
+
+impl Default for OtlpOptions {
+    fn default() -> Self {
+        OtlpOptions {
+            user_attribute: "enduser.id".to_string(),
+            action_attribute: "event.name".to_string(),
+            duration_attribute: "duration_ms".to_string(),
+        }
+    }
+}
+
+/// One line of an OTLP-JSON file export (ExportLogsServiceRequest or ExportTraceServiceRequest)
+/// At least one of resourceLogs and resourceSpans must be present
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ExportRequest {
+    resource_logs: Option<Vec<ResourceLogs>>,
+    resource_spans: Option<Vec<ResourceSpans>>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+struct Resource {
+    attributes: Vec<KeyValue>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+struct ResourceLogs {
+    resource: Resource,
+    scope_logs: Vec<ScopeLogs>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+struct ScopeLogs {
+    log_records: Vec<LogRecord>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+struct LogRecord {
+    time_unix_nano: Option<Value>,
+    observed_time_unix_nano: Option<Value>,
+    severity_number: u8,
+    severity_text: String,
+    body: Option<AnyValue>,
+    attributes: Vec<KeyValue>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+struct ResourceSpans {
+    resource: Resource,
+    scope_spans: Vec<ScopeSpans>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+struct ScopeSpans {
+    spans: Vec<Span>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+struct Span {
+    name: String,
+    start_time_unix_nano: Option<Value>,
+    end_time_unix_nano: Option<Value>,
+    status: Status,
+    attributes: Vec<KeyValue>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct Status {
+    code: u8,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct KeyValue {
+    key: String,
+    value: AnyValue,
+}
+
+/// OTLP AnyValue, only the scalar variants are used
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+struct AnyValue {
+    string_value: Option<String>,
+    int_value: Option<Value>,
+    double_value: Option<f64>,
+    bool_value: Option<bool>,
+}
+
+/// Span status code ERROR
+const STATUS_CODE_ERROR: u8 = 2;
+
+impl AnyValue {
+    /// Returns the scalar value as a string
+    fn as_string(&self) -> Option<String> {
+        if let Some(ref s) = self.string_value {
+            return Some(s.clone());
+        }
+        if let Some(ref i) = self.int_value {
+            return integer(i).map(|i| i.to_string());
+        }
+        if let Some(d) = self.double_value {
+            return Some(d.to_string());
+        }
+        self.bool_value.map(|b| b.to_string())
+    }
+
+    /// Returns the value as a non-negative integer, rounding doubles
+    fn as_u64(&self) -> Option<u64> {
+        if let Some(ref i) = self.int_value {
+            return integer(i);
+        }
+        if let Some(d) = self.double_value {
+            return (d.is_finite() && d >= 0.0).then(|| d.round() as u64);
+        }
+        self.string_value.as_ref().and_then(|s| s.parse().ok())
+    }
+}
+
+/// Reads an OTLP 64-bit integer, encoded as JSON string or number
+fn integer(value: &Value) -> Option<u64> {
+    match value {
+        Value::String(s) => s.parse().ok(),
+        other => other.as_u64(),
+    }
+}
+
+/// Returns the attribute as a string, looking at the record first and the resource second
+fn attribute(record: &[KeyValue], resource: &Resource, key: &str) -> Option<String> {
+    record
+        .iter()
+        .chain(resource.attributes.iter())
+        .find(|kv| kv.key == key)
+        .and_then(|kv| kv.value.as_string())
+        .filter(|s| !s.is_empty())
+}
+
+/// Returns the scalar record attributes not mapped onto Event fields
+fn extra_attributes(record: &[KeyValue], options: &OtlpOptions) -> BTreeMap<String, Value> {
+    let mapped = [&options.user_attribute, &options.action_attribute, &options.duration_attribute];
+    record
+        .iter()
+        .filter(|kv| !mapped.contains(&&kv.key))
+        .filter_map(|kv| kv.value.as_string().map(|value| (kv.key.clone(), Value::String(value))))
+        .collect()
+}
+
+/// Converts unix nanoseconds to an RFC 3339 UTC timestamp
+fn nanos_to_ts(nanos: u64) -> String {
+    DateTime::from_timestamp_nanos(nanos.min(i64::MAX as u64) as i64)
+        .to_rfc3339_opts(chrono::SecondsFormat::AutoSi, true)
+}
+
+/// Maps OTLP severityNumber ranges to a level
+/// 1-4 TRACE, 5-8 DEBUG, 9-12 INFO, 13-16 WARN, 17-20 ERROR, 21-24 FATAL.
+/// Unspecified (0) falls back to severityText, then INFO
+fn severity_level(number: u8, text: &str, scale: &LevelScale) -> Level {
+    match number {
+        1..=4 => Level::Trace,
+        5..=8 => Level::Debug,
+        13..=16 => Level::Warn,
+        17..=20 => Level::Error,
+        21..=24 => Level::Fatal,
+        0 => scale.parse_level(text).unwrap_or(Level::Info),
+        _ => Level::Info,
+    }
+}
+
+fn log_event(
+    record: &LogRecord,
+    resource: &Resource,
+    options: &OtlpOptions,
+    scale: &LevelScale,
+) -> Result<Event, String> {
+    let nanos = record
+        .time_unix_nano
+        .as_ref()
+        .and_then(integer)
+        .filter(|n| *n > 0)
+        .or_else(|| record.observed_time_unix_nano.as_ref().and_then(integer))
+        .ok_or("log record has no timeUnixNano")?;
+
+    let user = attribute(&record.attributes, resource, &options.user_attribute)
+        .or_else(|| attribute(&[], resource, "service.name"))
+        .ok_or_else(|| format!("log record has no {} attribute", options.user_attribute))?;
+    let action = attribute(&record.attributes, resource, &options.action_attribute)
+        .or_else(|| record.body.as_ref().and_then(AnyValue::as_string))
+        .ok_or_else(|| format!("log record has no {} attribute or body", options.action_attribute))?;
+    let duration_ms = match record.attributes.iter().find(|kv| kv.key == options.duration_attribute) {
+        Some(kv) => kv
+            .value
+            .as_u64()
+            .ok_or_else(|| format!("invalid {} attribute", options.duration_attribute))?,
+        None => 0,
+    };
+
+    Ok(Event {
+        ts: nanos_to_ts(nanos),
+        level: severity_level(record.severity_number, &record.severity_text, scale),
+        user,
+        action,
+        duration_ms,
+        extra: extra_attributes(&record.attributes, options),
+    })
+}
+
+fn span_event(span: &Span, resource: &Resource, options: &OtlpOptions) -> Result<Event, String> {
+    let start = span
+        .start_time_unix_nano
+        .as_ref()
+        .and_then(integer)
+        .ok_or("span has no startTimeUnixNano")?;
+    let end = span
+        .end_time_unix_nano
+        .as_ref()
+        .and_then(integer)
+        .ok_or("span has no endTimeUnixNano")?;
+    if end < start {
+        return Err("span ends before it starts".to_string());
+    }
+
+    let user = attribute(&span.attributes, resource, &options.user_attribute)
+        .or_else(|| attribute(&[], resource, "service.name"))
+        .ok_or_else(|| format!("span has no {} attribute", options.user_attribute))?;
+    let action = attribute(&span.attributes, resource, &options.action_attribute)
+        .or_else(|| (!span.name.is_empty()).then(|| span.name.clone()))
+        .ok_or("span has no name")?;
+
+    Ok(Event {
+        ts: nanos_to_ts(start),
+        level: if span.status.code == STATUS_CODE_ERROR {
+            Level::Error
+        } else {
+            Level::Info
+        },
+        user,
+        action,
+        duration_ms: (end - start) / 1_000_000,
+        extra: extra_attributes(&span.attributes, options),
+    })
+}
+
+/// Parses one line of an OTLP-JSON export into one result per log record and span
+/// Returns an error if the line is not an OTLP export request
+pub fn parse_line(
+    line: &str,
+    options: &OtlpOptions,
+    scale: &LevelScale,
+) -> Result<Vec<Result<Event, String>>, String> {
+    let request: ExportRequest =
+        serde_json::from_str(line).map_err(|e| format!("invalid OTLP JSON: {}", e))?;
+    if request.resource_logs.is_none() && request.resource_spans.is_none() {
+        return Err("not an OTLP export: missing resourceLogs and resourceSpans".to_string());
+    }
+
+    let mut events = Vec::new();
+    for resource_logs in request.resource_logs.iter().flatten() {
+        for scope in &resource_logs.scope_logs {
+            for record in &scope.log_records {
+                events.push(log_event(record, &resource_logs.resource, options, scale));
+            }
+        }
+    }
+    for resource_spans in request.resource_spans.iter().flatten() {
+        for scope in &resource_spans.scope_spans {
+            for span in &scope.spans {
+                events.push(span_event(span, &resource_spans.resource, options));
+            }
+        }
+    }
+
+    Ok(events)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_log_records() {
+        let line = r#"{"resourceLogs":[{"resource":{"attributes":[{"key":"service.name","value":{"stringValue":"checkout"}}]},
+            "scopeLogs":[{"logRecords":[
+                {"timeUnixNano":"1768824001000000000","severityNumber":17,"body":{"stringValue":"pay"},
+                 "attributes":[{"key":"enduser.id","value":{"stringValue":"alice"}},{"key":"duration_ms","value":{"intValue":"120"}}]},
+                {"timeUnixNano":"1768824002000000000","severityNumber":9,
+                 "attributes":[{"key":"event.name","value":{"stringValue":"login"}}]}
+            ]}]}]}"#
+            .replace('\n', "");
+
+        let events = parse_line(&line, &OtlpOptions::default(), &LevelScale::default()).unwrap();
+
+        assert_eq!(events.len(), 2);
+        let first = events[0].as_ref().unwrap();
+        assert_eq!(first.ts, "2026-01-19T12:00:01Z");
+        assert_eq!(first.level, Level::Error);
+        assert_eq!(first.user, "alice");
+        assert_eq!(first.action, "pay");
+        assert_eq!(first.duration_ms, 120);
+
+        let second = events[1].as_ref().unwrap();
+        assert_eq!(second.level, Level::Info);
+        assert_eq!(second.user, "checkout");
+        assert_eq!(second.action, "login");
+        assert_eq!(second.duration_ms, 0);
+    }
+
+    #[test]
+    fn test_spans() {
+        let line = r#"{"resourceSpans":[{"resource":{"attributes":[{"key":"service.name","value":{"stringValue":"api"}}]},
+            "scopeSpans":[{"spans":[
+                {"name":"GET /run","startTimeUnixNano":"1768824001000000000","endTimeUnixNano":"1768824001250000000","status":{"code":2}},
+                {"name":"broken","startTimeUnixNano":"5","endTimeUnixNano":"1"}
+            ]}]}]}"#
+            .replace('\n', "");
+
+        let events = parse_line(&line, &OtlpOptions::default(), &LevelScale::default()).unwrap();
+
+        let span = events[0].as_ref().unwrap();
+        assert_eq!(span.action, "GET /run");
+        assert_eq!(span.user, "api");
+        assert_eq!(span.level, Level::Error);
+        assert_eq!(span.duration_ms, 250);
+        assert!(events[1].is_err());
+    }
+
+    #[test]
+    fn test_missing_user() {
+        let line = r#"{"resourceLogs":[{"scopeLogs":[{"logRecords":[{"timeUnixNano":"1","body":{"stringValue":"x"}}]}]}]}"#;
+
+        let events = parse_line(line, &OtlpOptions::default(), &LevelScale::default()).unwrap();
+
+        assert!(events[0].as_ref().unwrap_err().contains("enduser.id"));
+    }
+
+    #[test]
+    fn test_pretty_printed_export_keeps_every_record() {
+        use crate::app::{App, Options};
+        use crate::cli::InputFormat;
+
+        let export = r#"{"resourceLogs":[{"scopeLogs":[{"logRecords":[
+            {"timeUnixNano":"1768824001000000000","attributes":[{"key":"enduser.id","value":{"stringValue":"alice"}}],
+             "body":{"stringValue":"pay"}},
+            {"timeUnixNano":"1768824002000000000","attributes":[{"key":"enduser.id","value":{"stringValue":"bob"}}],
+             "body":{"stringValue":"login"}}
+        ]}]}]}
+        {"resourceSpans":[]}
+        "#;
+        let path = "/tmp/eventsum_otlp_pretty.json";
+        std::fs::write(path, export).unwrap();
+
+        let mut app = App::new(Options {
+            format: InputFormat::OtlpJson,
+            ..Options::default()
+        });
+        app.read_from_file(path).unwrap();
+        std::fs::remove_file(path).ok();
+
+        // Both records are events, the empty export counts as one line
+        let result = app.get_result();
+        assert_eq!(result.events, 2);
+        assert_eq!(result.total_lines, 3);
+        assert_eq!(result.bad_lines, 0);
+    }
+
+    #[test]
+    fn test_invalid_line() {
+        assert!(parse_line("not-json", &OtlpOptions::default(), &LevelScale::default()).is_err());
+        assert!(parse_line("{}", &OtlpOptions::default(), &LevelScale::default()).is_err());
+        assert!(parse_line(r#"{"resourceLogs":[]}"#, &OtlpOptions::default(), &LevelScale::default())
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_plain_event_line_is_rejected() {
+        let line = r#"{"ts":"2026-01-19T12:00:01Z","level":"INFO","user":"alice","action":"login","duration_ms":10}"#;
+
+        let reason = parse_line(line, &OtlpOptions::default(), &LevelScale::default()).unwrap_err();
+
+        assert_eq!(reason, "not an OTLP export: missing resourceLogs and resourceSpans");
+    }
+}
@@ -0,0 +1,253 @@
+use crate::access_log::{self, AccessLogFormat};
+use crate::app::Options;
+use crate::cli::InputFormat;
+use crate::event::Event;
+use crate::lenient::{self, Coercions};
+use crate::mapping::FieldMapping;
+use crate::otlp::{self, OtlpOptions};
+use crate::pattern::LinePattern;
+use crate::severity::LevelScale;
+use crate::syslog;
+use chrono::{DateTime, Utc};
+use std::cell::{Cell, RefCell};
+
+/// Parses a single input line into an Event
+/// Implemented once per line-based input format. Level names are resolved against the scale
+pub trait LineParser {
+    /// Returns the event, or the reason the line is bad
+    fn parse(&self, line: &str, scale: &LevelScale) -> Result<Event, String>;
+
+    /// Parses a line that may hold several records, one result per record
+    /// Defaults to a single record per line
+    fn parse_records(&self, line: &str, scale: &LevelScale) -> Vec<Result<Event, String>> {
+        vec![self.parse(line, scale)]
+    }
+
+    /// Values coerced so far, only tracked by lenient parsers
+    fn coercions(&self) -> Option<Coercions> {
+        None
+    }
+}
+
+/// JSON Lines parser
+pub struct JsonParser;
+
+impl LineParser for JsonParser {
+    fn parse(&self, line: &str, scale: &LevelScale) -> Result<Event, String> {
+        Event::from_json_line(line, scale)
+    }
+}
+
+/// Lenient JSON parser, optionally with a field mapping
+pub struct LenientJsonParser {
+    pub mapping: FieldMapping,
+    pub coercions: RefCell<Coercions>,
+}
+
+impl LineParser for LenientJsonParser {
+    fn parse(&self, line: &str, scale: &LevelScale) -> Result<Event, String> {
+        lenient::parse_line(line, &self.mapping, scale, &mut self.coercions.borrow_mut())
+    }
+
+    fn coercions(&self) -> Option<Coercions> {
+        Some(self.coercions.borrow().clone())
+    }
+}
+
+/// logfmt parser
+pub struct LogfmtParser;
+
+impl LineParser for LogfmtParser {
+    fn parse(&self, line: &str, scale: &LevelScale) -> Result<Event, String> {
+        Event::from_logfmt_line(line, scale)
+    }
+}
+
+impl LineParser for FieldMapping {
+    fn parse(&self, line: &str, scale: &LevelScale) -> Result<Event, String> {
+        self.parse_line(line, scale)
+    }
+}
+
+impl LineParser for AccessLogFormat {
+    /// Levels come from the status code, the scale is not used
+    fn parse(&self, line: &str, _scale: &LevelScale) -> Result<Event, String> {
+        self.parse_line(line)
+    }
+}
+
+/// Syslog parser reading duration_ms from a configurable key
+/// RFC 3164 timestamps take their year from the previous event, the first from reference
+pub struct SyslogParser {
+    pub duration_key: String,
+    pub reference: Cell<DateTime<Utc>>,
+}
+
+impl LineParser for SyslogParser {
+    /// Levels come from the PRI severity, the scale is not used
+    fn parse(&self, line: &str, _scale: &LevelScale) -> Result<Event, String> {
+        let event = syslog::parse_line(line, &self.duration_key, self.reference.get())?;
+        if let Some(ts) = event.timestamp() {
+            self.reference.set(ts);
+        }
+        Ok(event)
+    }
+}
+
+/// OTLP-JSON export parser, each line holds any number of log records and spans
+pub struct OtlpParser {
+    pub options: OtlpOptions,
+}
+
+impl LineParser for OtlpParser {
+    /// Returns the first record of the line
+    fn parse(&self, line: &str, scale: &LevelScale) -> Result<Event, String> {
+        self.parse_records(line, scale)
+            .into_iter()
+            .next()
+            .unwrap_or_else(|| Err("no log records or spans".to_string()))
+    }
+
+    fn parse_records(&self, line: &str, scale: &LevelScale) -> Vec<Result<Event, String>> {
+        match otlp::parse_line(line, &self.options, scale) {
+            Ok(records) => records,
+            Err(reason) => vec![Err(reason)],
+        }
+    }
+}
+
+impl LineParser for LinePattern {
+    fn parse(&self, line: &str, scale: &LevelScale) -> Result<Event, String> {
+        self.parse_line(line, scale)
+    }
+}
+
+/// Builds the line parser for the options. A custom pattern overrides the input format
+/// CSV, TSV, MessagePack and CBOR are record-based and fall back to JSON here
+pub fn for_options(options: &Options) -> Box<dyn LineParser> {
+    if let Some(ref pattern) = options.pattern {
+        return Box::new(pattern.clone());
+    }
+
+    let access_log_preset = match options.format {
+        InputFormat::Logfmt => return Box::new(LogfmtParser),
+        InputFormat::Syslog => {
+            return Box::new(SyslogParser {
+                duration_key: options.syslog_duration_key.clone(),
+                reference: Cell::new(options.syslog_reference.unwrap_or_else(Utc::now)),
+            });
+        }
+        InputFormat::OtlpJson => {
+            return Box::new(OtlpParser {
+                options: options.otlp.clone(),
+            });
+        }
+        InputFormat::NginxCombined => access_log::NGINX_COMBINED,
+        InputFormat::ApacheCommon => access_log::APACHE_COMMON,
+        InputFormat::Json | InputFormat::JsonStream if options.lenient => {
+            return Box::new(LenientJsonParser {
+                mapping: options.field_mapping.clone().unwrap_or_default(),
+                coercions: RefCell::new(Coercions::default()),
+            });
+        }
+        InputFormat::Json | InputFormat::JsonStream => {
+            return match options.field_mapping {
+                Some(ref mapping) => Box::new(mapping.clone()),
+                None => Box::new(JsonParser),
+            };
+        }
+        InputFormat::Csv | InputFormat::Tsv | InputFormat::Msgpack | InputFormat::Cbor => {
+            return Box::new(JsonParser);
+        }
+    };
+
+    match options.log_format {
+        Some(ref format) => Box::new(format.clone()),
+        None => Box::new(
+            access_log_preset
+                .parse::<AccessLogFormat>()
+                .expect("built-in access log formats are valid"),
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parser_for_format() {
+        let options = Options {
+            format: InputFormat::Logfmt,
+            ..Options::default()
+        };
+        let parser = for_options(&options);
+
+        let event = parser
+            .parse(
+                "ts=2026-01-19T12:00:01Z level=info user=alice action=login duration_ms=1",
+                &options.level_scale,
+            )
+            .unwrap();
+        assert_eq!(event.user, "alice");
+        assert!(parser.parse(r#"{"ts":"2026-01-19T12:00:01Z"}"#, &options.level_scale).is_err());
+    }
+
+    #[test]
+    fn test_lenient_json_counts_coercions() {
+        let options = Options {
+            lenient: true,
+            ..Options::default()
+        };
+        let parser = for_options(&options);
+
+        let event = parser
+            .parse(
+                r#"{"ts":"2026-01-19T12:00:01Z","level":"info","user":"alice","action":"login","duration_ms":"7"}"#,
+                &options.level_scale,
+            )
+            .unwrap();
+
+        assert_eq!(event.duration_ms, 7);
+        let coercions = parser.coercions().unwrap();
+        assert_eq!(coercions.numeric_strings, 1);
+        assert_eq!(coercions.level_case, 1);
+        assert!(for_options(&Options::default()).coercions().is_none());
+    }
+
+    #[test]
+    fn test_mapping_is_strict_unless_lenient() {
+        let line = r#"{"ts":"2026-01-19T12:00:01Z","level":"info","user":42,"act":"login","duration_ms":1}"#;
+        let mut mapping = FieldMapping::default();
+        mapping.insert("action=act".parse().unwrap());
+        let strict = Options {
+            field_mapping: Some(mapping),
+            ..Options::default()
+        };
+        assert!(for_options(&strict).parse(line, &strict.level_scale).is_err());
+
+        let lenient = Options { lenient: true, ..strict };
+        let parser = for_options(&lenient);
+        let event = parser.parse(line, &lenient.level_scale).unwrap();
+
+        assert_eq!(event.action, "login");
+        let coercions = parser.coercions().unwrap();
+        assert_eq!(coercions.level_case, 1);
+        assert_eq!(coercions.numbers_to_strings, 1);
+    }
+
+    #[test]
+    fn test_pattern_overrides_format() {
+        let options = Options {
+            format: InputFormat::Syslog,
+            pattern: Some("%{NOTSPACE:ts} %{LOGLEVEL:level} %{WORD:user} %{WORD:action}".parse().unwrap()),
+            ..Options::default()
+        };
+        let parser = for_options(&options);
+
+        let event = parser
+            .parse("2026-01-19T12:00:01Z INFO alice login", &options.level_scale)
+            .unwrap();
+        assert_eq!(event.action, "login");
+    }
+}
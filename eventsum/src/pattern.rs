@@ -0,0 +1,237 @@
+use crate::event::{Event, Level};
+use crate::severity::LevelScale;
+use regex::Regex;
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::str::FromStr;
+
+/// Built-in Grok patterns usable as %{NAME} or %{NAME:field}
+const GROK_PATTERNS: &[(&str, &str)] = &[
+    ("INT", r"[+-]?\d+"),
+    ("POSINT", r"\b[1-9]\d*\b"),
+    ("NONNEGINT", r"\b\d+\b"),
+    ("NUMBER", r"[+-]?(?:\d+(?:\.\d*)?|\.\d+)"),
+    ("WORD", r"\b\w+\b"),
+    ("NOTSPACE", r"\S+"),
+    ("SPACE", r"\s*"),
+    ("DATA", r".*?"),
+    ("GREEDYDATA", r".*"),
+    ("QUOTEDSTRING", r#""(?:[^"\\]|\\.)*""#),
+    ("USERNAME", r"[a-zA-Z0-9._-]+"),
+    ("USER", r"%{USERNAME}"),
+    ("EMAILADDRESS", r"[a-zA-Z0-9._%+-]+@[a-zA-Z0-9.-]+"),
+    ("IPV4", r"(?:\d{1,3}\.){3}\d{1,3}"),
+    ("IPV6", r"[0-9A-Fa-f:]*:[0-9A-Fa-f:.]+"),
+    ("IP", r"(?:%{IPV6}|%{IPV4})"),
+    ("HOSTNAME", r"\b[0-9A-Za-z][0-9A-Za-z-]{0,62}(?:\.[0-9A-Za-z][0-9A-Za-z-]{0,62})*\.?\b"),
+    ("IPORHOST", r"(?:%{IP}|%{HOSTNAME})"),
+    ("URIPATH", r"(?:/[A-Za-z0-9$.+!*'(){},~:;=@#%&_\-]*)+"),
+    ("HTTPMETHOD", r"\b(?:GET|HEAD|POST|PUT|DELETE|CONNECT|OPTIONS|TRACE|PATCH)\b"),
+    ("LOGLEVEL", r"(?i:trace|debug|info|notice|warn(?:ing)?|err(?:or)?|crit(?:ical)?|fatal|emerg(?:ency)?|alert)"),
+    ("YEAR", r"\d{4}"),
+    ("MONTHNUM", r"(?:0?[1-9]|1[0-2])"),
+    ("MONTHDAY", r"(?:0[1-9]|[12]\d|3[01]|[1-9])"),
+    ("HOUR", r"(?:2[0123]|[01]?\d)"),
+    ("MINUTE", r"[0-5]\d"),
+    ("SECOND", r"(?:[0-5]?\d|60)(?:[.,]\d+)?"),
+    ("ISO8601_TIMEZONE", r"(?:Z|[+-]%{HOUR}(?::?%{MINUTE}))"),
+    (
+        "TIMESTAMP_ISO8601",
+        r"%{YEAR}-%{MONTHNUM}-%{MONTHDAY}[T ]%{HOUR}:?%{MINUTE}(?::?%{SECOND})?%{ISO8601_TIMEZONE}?",
+    ),
+];
+
+/// Groups every pattern must capture. duration_ms is optional and defaults to 0
+const REQUIRED_GROUPS: [&str; 4] = ["ts", "level", "user", "action"];
+
+/// Maximum nesting of %{...} references, guards against cycles
+const MAX_GROK_DEPTH: usize = 16;
+
+/// Expands %{NAME} and %{NAME:field} references into a plain regex
+fn expand_grok(pattern: &str, depth: usize) -> Result<String, String> {
+    if depth > MAX_GROK_DEPTH {
+        return Err("Grok patterns nested too deeply".to_string());
+    }
+
+    let mut expanded = String::new();
+    let mut rest = pattern;
+    while let Some(start) = rest.find("%{") {
+        expanded.push_str(&rest[..start]);
+        let end = rest[start..]
+            .find('}')
+            .ok_or_else(|| format!("unterminated Grok reference in '{}'", &rest[start..]))?;
+        let reference = &rest[start + 2..start + end];
+
+        // %{NAME:field} or %{NAME:field:type}, the type is ignored
+        let mut parts = reference.splitn(3, ':');
+        let name = parts.next().unwrap_or_default();
+        let field = parts.next();
+
+        let (_, body) = GROK_PATTERNS
+            .iter()
+            .find(|(known, _)| *known == name)
+            .ok_or_else(|| format!("unknown Grok pattern '{}'", name))?;
+        let body = expand_grok(body, depth + 1)?;
+
+        match field {
+            Some(field) => expanded.push_str(&format!("(?P<{}>{})", field, body)),
+            None => expanded.push_str(&format!("(?:{})", body)),
+        }
+        rest = &rest[start + end + 1..];
+    }
+    expanded.push_str(rest);
+
+    Ok(expanded)
+}
+
+/// Custom line pattern: a regex with named groups, optionally using Grok references
+/// Named groups ts, level, user, action and duration_ms populate the Event fields,
+/// other named groups become string extra fields
+#[derive(Debug, Clone)]
+pub struct LinePattern {
+    /// Pattern as given, used in bad-line reasons
+    source: String,
+    regex: Regex,
+}
+
+impl FromStr for LinePattern {
+    type Err = String;
+
+    /// Compiles a pattern such as `%{TIMESTAMP_ISO8601:ts} %{LOGLEVEL:level} (?P<user>\w+) ...`
+    fn from_str(pattern: &str) -> Result<Self, Self::Err> {
+        let expanded = expand_grok(pattern, 0)?;
+        let regex = Regex::new(&format!("^(?:{})$", expanded)).map_err(|e| format!("invalid pattern: {}", e))?;
+
+        let names: Vec<&str> = regex.capture_names().flatten().collect();
+        for required in REQUIRED_GROUPS {
+            if !names.contains(&required) {
+                return Err(format!("pattern must have a named group '{}'", required));
+            }
+        }
+
+        Ok(LinePattern {
+            source: pattern.to_string(),
+            regex,
+        })
+    }
+}
+
+impl LinePattern {
+    /// Parses a line into an Event using the named captures
+    /// Returns the reason if the line does not match or a captured field is malformed
+    pub fn parse_line(&self, line: &str, scale: &LevelScale) -> Result<Event, String> {
+        let caps = self
+            .regex
+            .captures(line)
+            .ok_or_else(|| format!("line does not match pattern '{}'", self.source))?;
+        let field = |name: &str| caps.name(name).map(|m| m.as_str()).unwrap_or_default();
+
+        // LOGLEVEL also matches aliases such as WARNING and ERR
+        let level = match Level::from_alias(field("level")) {
+            Some(level) => level,
+            None => scale.parse_level(field("level"))?,
+        };
+        let extra: BTreeMap<String, Value> = self
+            .regex
+            .capture_names()
+            .flatten()
+            .filter(|name| !REQUIRED_GROUPS.contains(name) && *name != "duration_ms")
+            .filter_map(|name| caps.name(name).map(|m| (name.to_string(), Value::String(m.as_str().to_string()))))
+            .collect();
+        let duration_ms = match caps.name("duration_ms") {
+            Some(m) => m
+                .as_str()
+                .parse()
+                .map_err(|_| format!("invalid duration_ms '{}'", m.as_str()))?,
+            None => 0,
+        };
+
+        Ok(Event {
+            ts: field("ts").to_string(),
+            level,
+            user: field("user").to_string(),
+            action: field("action").to_string(),
+            duration_ms,
+            extra,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_plain_regex_pattern() {
+        let pattern: LinePattern = r"(?P<ts>\S+) \[(?P<level>\w+)\] (?P<user>\w+) did (?P<action>\w+) in (?P<duration_ms>\d+)ms"
+            .parse()
+            .unwrap();
+
+        let event = pattern
+            .parse_line("2026-01-19T12:00:01Z [warn] alice did login in 42ms", &LevelScale::default())
+            .unwrap();
+
+        assert_eq!(event.ts, "2026-01-19T12:00:01Z");
+        assert_eq!(event.level, Level::Warn);
+        assert_eq!(event.user, "alice");
+        assert_eq!(event.action, "login");
+        assert_eq!(event.duration_ms, 42);
+    }
+
+    #[test]
+    fn test_grok_pattern() {
+        let pattern: LinePattern =
+            "%{TIMESTAMP_ISO8601:ts} %{LOGLEVEL:level} %{USER:user} %{WORD:action}%{GREEDYDATA}"
+                .parse()
+                .unwrap();
+
+        let event = pattern
+            .parse_line("2026-01-19 12:00:01+01:00 ERROR bob.smith upload failed hard", &LevelScale::default())
+            .unwrap();
+
+        assert_eq!(event.ts, "2026-01-19 12:00:01+01:00");
+        assert_eq!(event.level, Level::Error);
+        assert_eq!(event.user, "bob.smith");
+        assert_eq!(event.action, "upload");
+        assert_eq!(event.duration_ms, 0);
+    }
+
+    #[test]
+    fn test_grok_level_aliases() {
+        let pattern: LinePattern = "%{TIMESTAMP_ISO8601:ts} %{LOGLEVEL:level} %{USER:user} %{WORD:action}"
+            .parse()
+            .unwrap();
+
+        let warning = pattern
+            .parse_line("2026-01-19T12:00:01Z WARNING alice login", &LevelScale::default())
+            .unwrap();
+        let err = pattern
+            .parse_line("2026-01-19T12:00:01Z err alice login", &LevelScale::default())
+            .unwrap();
+
+        assert_eq!(warning.level, Level::Warn);
+        assert_eq!(err.level, Level::Error);
+    }
+
+    #[test]
+    fn test_pattern_mismatch_reason() {
+        let pattern: LinePattern = "%{NOTSPACE:ts} %{WORD:level} %{WORD:user} %{WORD:action}"
+            .parse()
+            .unwrap();
+
+        let err = pattern.parse_line("just garbage", &LevelScale::default()).unwrap_err();
+        assert!(err.contains("does not match pattern"));
+
+        let err = pattern.parse_line("ts VERBOSE alice login", &LevelScale::default()).unwrap_err();
+        assert!(err.contains("unknown level"));
+    }
+
+    #[test]
+    fn test_invalid_patterns() {
+        assert!("%{NOPE:ts}".parse::<LinePattern>().is_err());
+        assert!("%{WORD:ts".parse::<LinePattern>().is_err());
+        // Missing the action group
+        assert!("%{WORD:ts} %{WORD:level} %{WORD:user}".parse::<LinePattern>().is_err());
+        assert!("(?P<ts>[) %{WORD:level}".parse::<LinePattern>().is_err());
+    }
+}
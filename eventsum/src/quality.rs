@@ -0,0 +1,187 @@
+use crate::event::{Event, Level};
+use crate::stats;
+use chrono::{DateTime, TimeDelta, Utc};
+use log::warn;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::time::Duration;
+
+/// Data quality counters over the stream of valid events
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DataQuality {
+    /// Events identical to an earlier event (same ts, level, user, action and duration)
+    pub duplicates: usize,
+    /// True if duplicates were excluded from the aggregation
+    pub duplicates_excluded: bool,
+    /// Events with a timestamp earlier than the previous event
+    pub out_of_order: usize,
+    /// Gap threshold in milliseconds, if one was given
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_gap_ms: Option<u64>,
+    /// Gaps between consecutive events larger than max_gap_ms
+    pub gaps_over_max: usize,
+    /// Largest forward gap between consecutive events in milliseconds
+    pub largest_gap_ms: u64,
+    /// Events whose ts could not be parsed (skipped for ordering checks)
+    pub unparsed_timestamps: usize,
+}
+
+/// Fields that identify a duplicate event, extra fields are ignored
+type EventKey = (String, Level, String, String, u64);
+
+/// Tracks duplicates, ordering and gaps while events are read
+pub struct QualityTracker {
+    seen: HashSet<EventKey>,
+    previous_ts: Option<DateTime<Utc>>,
+    max_gap: Option<TimeDelta>,
+    quality: DataQuality,
+    /// True once a counter saturated
+    overflowed: bool,
+}
+
+impl QualityTracker {
+    /// Creates a tracker with an optional gap threshold
+    pub fn new(max_gap: Option<Duration>, dedupe: bool) -> Self {
+        QualityTracker {
+            seen: HashSet::new(),
+            previous_ts: None,
+            max_gap: max_gap.map(|gap| TimeDelta::from_std(gap).unwrap_or(TimeDelta::MAX)),
+            quality: DataQuality {
+                duplicates_excluded: dedupe,
+                max_gap_ms: max_gap.map(|gap| gap.as_millis().try_into().unwrap_or(u64::MAX)),
+                ..DataQuality::default()
+            },
+            overflowed: false,
+        }
+    }
+
+    /// Records an event in input order
+    /// Returns true if the event is an exact duplicate of an earlier one
+    pub fn observe(&mut self, event: &Event) -> bool {
+        let key = (
+            event.ts.clone(),
+            event.level.clone(),
+            event.user.clone(),
+            event.action.clone(),
+            event.duration_ms,
+        );
+        let duplicate = !self.seen.insert(key);
+        if duplicate {
+            warn!("Duplicate event: {} {} {}", event.ts, event.user, event.action);
+            self.overflowed |= stats::add_count(&mut self.quality.duplicates, 1);
+        }
+
+        let Some(ts) = event.timestamp() else {
+            self.overflowed |= stats::add_count(&mut self.quality.unparsed_timestamps, 1);
+            return duplicate;
+        };
+
+        if let Some(previous) = self.previous_ts {
+            let gap = ts - previous;
+            if gap < TimeDelta::zero() {
+                warn!("Out-of-order event: {} is before {}", event.ts, previous.to_rfc3339());
+                self.overflowed |= stats::add_count(&mut self.quality.out_of_order, 1);
+            } else {
+                let gap_ms = gap.num_milliseconds() as u64;
+                self.quality.largest_gap_ms = self.quality.largest_gap_ms.max(gap_ms);
+                if self.max_gap.is_some_and(|max| gap > max) {
+                    self.overflowed |= stats::add_count(&mut self.quality.gaps_over_max, 1);
+                }
+            }
+        }
+        self.previous_ts = Some(ts);
+
+        duplicate
+    }
+
+    /// Returns the collected counters
+    pub fn summary(&self) -> DataQuality {
+        self.quality.clone()
+    }
+
+    /// True if a counter saturated at usize::MAX
+    pub fn overflowed(&self) -> bool {
+        self.overflowed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::event::Level;
+
+    fn event(ts: &str, user: &str) -> Event {
+        Event { ts: ts.to_string(), ..Event::test(user, "test", Level::Info) }
+    }
+
+    #[test]
+    fn test_duplicates() {
+        let mut tracker = QualityTracker::new(None, false);
+
+        assert!(!tracker.observe(&event("2026-01-19T12:00:00Z", "alice")));
+        assert!(!tracker.observe(&event("2026-01-19T12:00:00Z", "bob")));
+        assert!(tracker.observe(&event("2026-01-19T12:00:00Z", "alice")));
+
+        assert_eq!(tracker.summary().duplicates, 1);
+    }
+
+    #[test]
+    fn test_duplicates_ignore_extra_fields() {
+        let mut tracker = QualityTracker::new(None, true);
+        let mut first = event("2026-01-19T12:00:00Z", "alice");
+        first.extra.insert("request_id".to_string(), "a1".into());
+        let mut second = first.clone();
+        second.extra.insert("request_id".to_string(), "b2".into());
+
+        assert!(!tracker.observe(&first));
+        assert!(tracker.observe(&second));
+        assert_eq!(tracker.summary().duplicates, 1);
+    }
+
+    #[test]
+    fn test_counters_saturate() {
+        let mut tracker = QualityTracker::new(None, false);
+        tracker.quality.unparsed_timestamps = usize::MAX;
+
+        tracker.observe(&event("garbage", "alice"));
+
+        assert_eq!(tracker.summary().unparsed_timestamps, usize::MAX);
+        assert!(tracker.overflowed());
+    }
+
+    #[test]
+    fn test_out_of_order() {
+        let mut tracker = QualityTracker::new(None, false);
+
+        tracker.observe(&event("2026-01-19T12:00:10Z", "alice"));
+        tracker.observe(&event("2026-01-19T12:00:05Z", "alice"));
+        tracker.observe(&event("2026-01-19T12:00:06Z", "alice"));
+
+        let quality = tracker.summary();
+        assert_eq!(quality.out_of_order, 1);
+        assert_eq!(quality.largest_gap_ms, 1000);
+    }
+
+    #[test]
+    fn test_gaps_over_max() {
+        let mut tracker = QualityTracker::new(Some(Duration::from_secs(60)), false);
+
+        tracker.observe(&event("2026-01-19T12:00:00Z", "alice"));
+        tracker.observe(&event("2026-01-19T12:01:00Z", "alice"));
+        tracker.observe(&event("2026-01-19T12:05:00Z", "alice"));
+
+        let quality = tracker.summary();
+        assert_eq!(quality.gaps_over_max, 1);
+        assert_eq!(quality.max_gap_ms, Some(60_000));
+        assert_eq!(quality.largest_gap_ms, 240_000);
+    }
+
+    #[test]
+    fn test_unparsed_timestamp() {
+        let mut tracker = QualityTracker::new(None, false);
+
+        tracker.observe(&event("garbage", "alice"));
+
+        assert_eq!(tracker.summary().unparsed_timestamps, 1);
+    }
+}
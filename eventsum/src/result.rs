@@ -1,6 +1,17 @@
+use crate::apdex::ApdexSummary;
 use crate::event::{Event, Level};
-use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use crate::examples::Examples;
+use crate::field_stats::FieldStats;
+use crate::lenient::Coercions;
+use crate::quality::DataQuality;
+use crate::rules::ValidationSummary;
+use crate::schema::SchemaViolations;
+use crate::severity::LevelScale;
+use crate::sampling::SamplingSummary;
+use crate::stats;
+use serde::ser::SerializeMap;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::collections::{BTreeMap, HashMap};
 
 /// User count for top users ranking
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -9,15 +20,101 @@ pub struct UserCount {
     pub count: usize,
 }
 
-/// Level counts structure
+/// Event count and p95 duration of a single action
 #[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ActionStats {
+    pub events: usize,
+    pub p95_duration_ms: u64,
+}
+
+/// Event counts per level name
+///
+/// Serialized as a map with INFO, WARN and ERROR first (always present, for compatibility
+/// with the original three-level summary), then the other levels seen in order of severity
+#[derive(Debug, Clone, Default, PartialEq)]
 pub struct LevelCounts {
-    #[serde(rename = "INFO")]
-    pub info: usize,
-    #[serde(rename = "WARN")]
-    pub warn: usize,
-    #[serde(rename = "ERROR")]
-    pub error: usize,
+    counts: HashMap<String, usize>,
+    /// Severity of the levels added, unknown for names read from a saved summary
+    severities: HashMap<String, u8>,
+}
+
+/// Levels always present in by_level
+const FIXED_LEVELS: [&str; 3] = ["INFO", "WARN", "ERROR"];
+
+impl LevelCounts {
+    /// Adds count events of the level, saturating at usize::MAX
+    /// Returns true if the count overflowed
+    pub fn add(&mut self, level: &Level, count: usize) -> bool {
+        self.severities.insert(level.name().to_string(), level.severity());
+        stats::add_count(self.counts.entry(level.name().to_string()).or_insert(0), count)
+    }
+
+    /// Returns the count of a level name, 0 if none were seen
+    pub fn get(&self, name: &str) -> usize {
+        self.counts.get(name).copied().unwrap_or(0)
+    }
+
+    /// Returns the severity of a level name, looked up in the scale if the level was not added
+    fn severity(&self, name: &str, scale: &LevelScale) -> Option<u8> {
+        self.severities
+            .get(name)
+            .copied()
+            .or_else(|| scale.parse_level(name).ok().map(|level| level.severity()))
+    }
+
+    /// Counts of ERROR and more severe levels
+    /// Custom levels of a loaded summary are resolved against the scale
+    pub fn errors(&self, scale: &LevelScale) -> usize {
+        self.counts
+            .iter()
+            .filter(|(name, _)| {
+                self.severity(name, scale)
+                    .is_some_and(|severity| severity >= Level::Error.severity())
+            })
+            .fold(0, |sum: usize, (_, count)| sum.saturating_add(*count))
+    }
+
+    /// Level names and counts in serialization order
+    pub fn ordered(&self) -> Vec<(&str, usize)> {
+        let mut others: Vec<(u8, &str, usize)> = self
+            .counts
+            .iter()
+            .filter(|(name, _)| !FIXED_LEVELS.contains(&name.as_str()))
+            .map(|(name, count)| {
+                // Unknown names (e.g. from a saved summary) go last
+                let severity = self.severity(name, &LevelScale::default()).unwrap_or(u8::MAX);
+                (severity, name.as_str(), *count)
+            })
+            .collect();
+        others.sort();
+
+        FIXED_LEVELS
+            .iter()
+            .map(|name| (*name, self.get(name)))
+            .chain(others.into_iter().map(|(_, name, count)| (name, count)))
+            .collect()
+    }
+}
+
+impl Serialize for LevelCounts {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let ordered = self.ordered();
+        let mut map = serializer.serialize_map(Some(ordered.len()))?;
+        for (name, count) in ordered {
+            map.serialize_entry(name, &count)?;
+        }
+        map.end()
+    }
+}
+
+impl<'de> Deserialize<'de> for LevelCounts {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let counts = HashMap::deserialize(deserializer)?;
+        Ok(LevelCounts {
+            counts,
+            severities: HashMap::new(),
+        })
+    }
 }
 
 /// Summary result structure for event processing
@@ -27,6 +124,9 @@ pub struct SummaryResult {
     pub total_lines: usize,
     /// Count of invalid lines
     pub bad_lines: usize,
+    /// Count of blank or whitespace-only lines, not part of total_lines unless counted as bad
+    #[serde(default)]
+    pub blank_lines: usize,
     /// Count of valid events
     pub events: usize,
     /// Counts per log level
@@ -37,6 +137,37 @@ pub struct SummaryResult {
     pub p95_duration_ms: u64,
     /// Event with the largest duration_ms
     pub outlier: Option<Event>,
+    /// Per-action stats, only present when requested
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub by_action: Option<BTreeMap<String, ActionStats>>,
+    /// Apdex scores, only present when a threshold was given
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub apdex: Option<ApdexSummary>,
+    /// Duplicate, ordering and gap checks, only present when requested
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub data_quality: Option<DataQuality>,
+    /// Values coerced in lenient mode, only present with --lenient
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub coercions: Option<Coercions>,
+    /// Stats of extra fields, only present when requested
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub field_stats: Option<BTreeMap<String, FieldStats>>,
+    /// Validation rule violations, only present with --rules
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub validation: Option<ValidationSummary>,
+    /// Events rejected by the JSON Schema, only present with --schema
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub schema_violations: Option<SchemaViolations>,
+    /// Sampling mode and estimated full counts, only present when sampling
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sampling: Option<SamplingSummary>,
+    /// Example events per level, top user and top action, only present with --examples
+    /// Not read back from saved summaries, their custom levels would need the scale
+    #[serde(skip_serializing_if = "Option::is_none", skip_deserializing)]
+    pub examples: Option<Examples>,
+    /// A counter reached usize::MAX and stopped there, only present if it happened
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub overflowed: bool,
 }
 
 impl SummaryResult {
@@ -45,40 +176,48 @@ impl SummaryResult {
         SummaryResult {
             total_lines: 0,
             bad_lines: 0,
+            blank_lines: 0,
             events: 0,
-            by_level: LevelCounts {
-                info: 0,
-                warn: 0,
-                error: 0,
-            },
+            by_level: LevelCounts::default(),
             top_users: Vec::new(),
             p95_duration_ms: 0,
             outlier: None,
+            by_action: None,
+            apdex: None,
+            data_quality: None,
+            coercions: None,
+            field_stats: None,
+            validation: None,
+            schema_violations: None,
+            sampling: None,
+            examples: None,
+            overflowed: false,
         }
     }
     
     /// Increments the total_lines counter
     pub fn increment_total_lines(&mut self) {
-        self.total_lines += 1;
+        self.overflowed |= stats::add_count(&mut self.total_lines, 1);
     }
     
     /// Increments the bad_lines counter    
     pub fn increment_bad_lines(&mut self) {
-        self.bad_lines += 1;
+        self.overflowed |= stats::add_count(&mut self.bad_lines, 1);
+    }
+
+    /// Increments the blank_lines counter
+    pub fn increment_blank_lines(&mut self) {
+        self.overflowed |= stats::add_count(&mut self.blank_lines, 1);
     }
 
     /// Increments the events counter
     pub fn increment_events(&mut self) {
-        self.events += 1;
+        self.overflowed |= stats::add_count(&mut self.events, 1);
     }
 
     /// Updates level counts based on the event's level
-    pub fn update_level_counts(&mut self, level: Level) {
-        match level {
-            Level::Info => self.by_level.info += 1,
-            Level::Warn => self.by_level.warn += 1,
-            Level::Error => self.by_level.error += 1,
-        }
+    pub fn update_level_counts(&mut self, level: &Level) {
+        self.overflowed |= self.by_level.add(level, 1);
     }
     
     /// Computes top users from a HashMap of user counts
@@ -116,7 +255,7 @@ impl SummaryResult {
     }
     
     /// Computes the 95th percentile of durations using the nearest-rank method
-    /// rank = ceil(0.95 * n) (1-based), then p95 = d[rank - 1]
+    /// rank = ceil(95 * n / 100) (1-based, exact integer arithmetic), then p95 = d[rank - 1]
     /// Returns 0 if no events
     pub fn compute_p95_duration(&mut self, events: &[Event]) {
         if events.is_empty() {
@@ -128,9 +267,9 @@ impl SummaryResult {
         let mut durations: Vec<u64> = events.iter().map(|e| e.duration_ms).collect();
         durations.sort_unstable();
         
-        // Calculate rank using nearest-rank method
+        // Calculate rank using nearest-rank method, exactly in integer arithmetic
         let n = durations.len();
-        let rank = ((0.95 * n as f64).ceil() as usize).max(1);
+        let rank = (95 * n as u128).div_ceil(100).max(1) as usize;
         
         // Get p95 (convert from 1-based to 0-based index)
         self.p95_duration_ms = durations[rank - 1];
@@ -141,6 +280,36 @@ impl SummaryResult {
         self.outlier = events.iter().max_by_key(|e| e.duration_ms).cloned();
     }
     
+    /// Computes event count and p95 duration per action
+    pub fn compute_by_action(&mut self, events: &[Event]) {
+        let mut durations: BTreeMap<String, Vec<u64>> = BTreeMap::new();
+        for event in events {
+            durations
+                .entry(event.action.clone())
+                .or_default()
+                .push(event.duration_ms);
+        }
+
+        let by_action = durations
+            .into_iter()
+            .map(|(action, mut durations)| {
+                durations.sort_unstable();
+                let stats = ActionStats {
+                    events: durations.len(),
+                    p95_duration_ms: stats::nearest_rank(&durations, 95),
+                };
+                (action, stats)
+            })
+            .collect();
+
+        self.by_action = Some(by_action);
+    }
+
+    /// Computes Apdex scores overall and per action for threshold T
+    pub fn compute_apdex(&mut self, events: &[Event], threshold_ms: u64) {
+        self.apdex = Some(ApdexSummary::from_events(events, threshold_ms));
+    }
+
     /// Serializes to JSON string
     pub fn to_json(&self, pretty: bool) -> Result<String, serde_json::Error> {
         if pretty {
@@ -154,6 +323,7 @@ impl SummaryResult {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use proptest::prelude::*;
 
     #[test]
     fn test_compute_top_users_basic() {
@@ -269,6 +439,7 @@ mod tests {
                 user: "alice".to_string(),
                 action: "test".to_string(),
                 duration_ms: 100,
+                extra: BTreeMap::new(),
             }
         ];
         
@@ -288,6 +459,7 @@ mod tests {
                 user: "alice".to_string(),
                 action: "test".to_string(),
                 duration_ms: 120,
+                extra: BTreeMap::new(),
             },
             Event {
                 ts: "2026-01-19T12:00:02Z".to_string(),
@@ -295,6 +467,7 @@ mod tests {
                 user: "bob".to_string(),
                 action: "test".to_string(),
                 duration_ms: 400,
+                extra: BTreeMap::new(),
             },
             Event {
                 ts: "2026-01-19T12:00:03Z".to_string(),
@@ -302,6 +475,7 @@ mod tests {
                 user: "alice".to_string(),
                 action: "test".to_string(),
                 duration_ms: 900,
+                extra: BTreeMap::new(),
             },
             Event {
                 ts: "2026-01-19T12:00:04Z".to_string(),
@@ -309,6 +483,7 @@ mod tests {
                 user: "carol".to_string(),
                 action: "test".to_string(),
                 duration_ms: 20,
+                extra: BTreeMap::new(),
             },
             Event {
                 ts: "2026-01-19T12:00:05Z".to_string(),
@@ -316,6 +491,7 @@ mod tests {
                 user: "alice".to_string(),
                 action: "test".to_string(),
                 duration_ms: 10,
+                extra: BTreeMap::new(),
             },
         ];
         
@@ -346,6 +522,7 @@ mod tests {
                 user: "alice".to_string(),
                 action: "test".to_string(),
                 duration_ms: 120,
+                extra: BTreeMap::new(),
             },
             Event {
                 ts: "2026-01-19T12:00:02Z".to_string(),
@@ -353,6 +530,7 @@ mod tests {
                 user: "bob".to_string(),
                 action: "slow_task".to_string(),
                 duration_ms: 900,
+                extra: BTreeMap::new(),
             },
             Event {
                 ts: "2026-01-19T12:00:03Z".to_string(),
@@ -360,6 +538,7 @@ mod tests {
                 user: "carol".to_string(),
                 action: "test".to_string(),
                 duration_ms: 50,
+                extra: BTreeMap::new(),
             },
         ];
         
@@ -372,6 +551,23 @@ mod tests {
         assert_eq!(outlier.action, "slow_task");
     }
     
+    #[test]
+    fn test_compute_by_action() {
+        let mut result = SummaryResult::new();
+        let events: Vec<Event> = [("login", 20), ("run_script", 120), ("run_script", 900)]
+            .iter()
+            .map(|&(action, duration_ms)| Event { duration_ms, ..Event::test("alice", action, Level::Info) })
+            .collect();
+
+        result.compute_by_action(&events);
+
+        let by_action = result.by_action.unwrap();
+        assert_eq!(by_action.len(), 2);
+        assert_eq!(by_action["login"].events, 1);
+        assert_eq!(by_action["run_script"].events, 2);
+        assert_eq!(by_action["run_script"].p95_duration_ms, 900);
+    }
+
     #[test]
     fn test_compute_outlier_empty() {
         let mut result = SummaryResult::new();
@@ -381,6 +577,76 @@ mod tests {
         
         assert!(result.outlier.is_none());
     }
-}
 
+    #[test]
+    fn test_level_counts_order() {
+        let mut counts = LevelCounts::default();
+        counts.add(&Level::Fatal, 1);
+        counts.add(&Level::Debug, 2);
+        counts.add(&Level::Error, 3);
+        counts.add(&Level::Critical, 4);
+
+        let json = serde_json::to_string(&counts).unwrap();
+        assert_eq!(json, r#"{"INFO":0,"WARN":0,"ERROR":3,"DEBUG":2,"CRITICAL":4,"FATAL":1}"#);
+        assert_eq!(counts.errors(&LevelScale::default()), 8);
+
+        let parsed: LevelCounts = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.get("CRITICAL"), 4);
+        assert_eq!(parsed.errors(&LevelScale::default()), 8);
+    }
 
+    #[test]
+    fn test_custom_level_errors() {
+        let mut counts = LevelCounts::default();
+        counts.add(&Level::Error, 1);
+        counts.add(
+            &Level::Custom {
+                name: "SEVERE".to_string(),
+                severity: 18,
+            },
+            2,
+        );
+        assert_eq!(counts.errors(&LevelScale::default()), 3);
+
+        // A saved summary only has names, custom levels need the scale
+        let parsed: LevelCounts = serde_json::from_str(&serde_json::to_string(&counts).unwrap()).unwrap();
+        assert_eq!(parsed.errors(&LevelScale::default()), 1);
+        assert_eq!(parsed.errors(&LevelScale::from_toml_str("SEVERE = 18").unwrap()), 3);
+        assert_eq!(parsed.errors(&LevelScale::from_toml_str("SEVERE = 3").unwrap()), 1);
+    }
+
+    #[test]
+    fn test_counters_saturate_and_flag_overflow() {
+        let mut result = SummaryResult::new();
+        result.total_lines = usize::MAX;
+
+        result.increment_total_lines();
+        result.increment_bad_lines();
+
+        assert_eq!(result.total_lines, usize::MAX);
+        assert_eq!(result.bad_lines, 1);
+        assert!(result.overflowed);
+        assert!(serde_json::to_string(&result).unwrap().contains(r#""overflowed":true"#));
+        assert!(!serde_json::to_string(&SummaryResult::new()).unwrap().contains("overflowed"));
+    }
+
+    proptest! {
+        /// p95 is the smallest duration with at least 95% of the durations at or below it
+        #[test]
+        fn prop_p95_matches_definition(durations in prop::collection::vec(prop_oneof![Just(u64::MAX), Just(0u64), any::<u64>()], 1..300)) {
+            let events: Vec<Event> = durations
+                .iter()
+                .map(|&duration_ms| Event { duration_ms, ..Event::test("alice", "test", Level::Info) })
+                .collect();
+            let mut result = SummaryResult::new();
+
+            result.compute_p95_duration(&events);
+            let p95 = result.p95_duration_ms;
+            let at_or_below = durations.iter().filter(|&&d| d <= p95).count();
+            let below = durations.iter().filter(|&&d| d < p95).count();
+
+            prop_assert!(at_or_below * 100 >= 95 * durations.len());
+            prop_assert!(below * 100 < 95 * durations.len());
+        }
+    }
+}
@@ -0,0 +1,386 @@
+use crate::event::Event;
+use crate::stats;
+use chrono::{DateTime, TimeDelta, Utc};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::time::Duration;
+
+/// Rule names, in evaluation order
+pub const RULE_NAMES: [&str; 6] = [
+    "user_pattern",
+    "allowed_actions",
+    "max_duration",
+    "no_future_ts",
+    "max_ts_age",
+    "max_field_length",
+];
+
+fn enabled() -> bool {
+    true
+}
+
+/// Parses a humantime duration such as `24h` or `30d`
+fn duration(rule: &str, value: &str) -> Result<TimeDelta, String> {
+    let duration: Duration =
+        humantime::parse_duration(value).map_err(|e| format!("{}: invalid duration '{}': {}", rule, value, e))?;
+    TimeDelta::from_std(duration).map_err(|_| format!("{}: duration '{}' out of range", rule, value))
+}
+
+/// Rules file, one optional table per rule. Every table accepts `enabled = false`
+///
+/// ```toml
+/// [user_pattern]
+/// pattern = "^[a-z][a-z0-9_]*$"
+/// [allowed_actions]
+/// actions = ["login", "logout"]
+/// [max_duration]
+/// max = "24h"
+/// [no_future_ts]
+/// tolerance = "5m"
+/// [max_ts_age]
+/// max = "30d"
+/// [max_field_length]
+/// max = 64
+/// ```
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RulesFile {
+    user_pattern: Option<UserPatternConfig>,
+    allowed_actions: Option<AllowedActionsConfig>,
+    max_duration: Option<MaxConfig>,
+    no_future_ts: Option<ToleranceConfig>,
+    max_ts_age: Option<MaxConfig>,
+    max_field_length: Option<MaxLengthConfig>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct UserPatternConfig {
+    pattern: String,
+    #[serde(default = "enabled")]
+    enabled: bool,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct AllowedActionsConfig {
+    actions: Vec<String>,
+    #[serde(default = "enabled")]
+    enabled: bool,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct MaxConfig {
+    max: String,
+    #[serde(default = "enabled")]
+    enabled: bool,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ToleranceConfig {
+    #[serde(default)]
+    tolerance: Option<String>,
+    #[serde(default = "enabled")]
+    enabled: bool,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct MaxLengthConfig {
+    max: usize,
+    #[serde(default = "enabled")]
+    enabled: bool,
+}
+
+/// A single compiled rule
+#[derive(Debug, Clone)]
+enum Rule {
+    /// user must match the regex
+    UserPattern(Regex),
+    /// action must be one of the list
+    AllowedActions(HashSet<String>),
+    /// duration_ms must not exceed the limit
+    MaxDuration(u64),
+    /// ts must not be later than now plus the tolerance
+    NoFutureTs(TimeDelta),
+    /// ts must not be older than the limit
+    MaxTsAge(TimeDelta),
+    /// user and action must not be longer than the limit, in characters
+    MaxFieldLength(usize),
+}
+
+impl Rule {
+    fn name(&self) -> &'static str {
+        match self {
+            Rule::UserPattern(_) => "user_pattern",
+            Rule::AllowedActions(_) => "allowed_actions",
+            Rule::MaxDuration(_) => "max_duration",
+            Rule::NoFutureTs(_) => "no_future_ts",
+            Rule::MaxTsAge(_) => "max_ts_age",
+            Rule::MaxFieldLength(_) => "max_field_length",
+        }
+    }
+
+    /// Returns the reason if the event breaks the rule
+    /// Time rules pass events whose ts cannot be parsed
+    fn check(&self, event: &Event, now: DateTime<Utc>) -> Result<(), String> {
+        match self {
+            Rule::UserPattern(regex) if !regex.is_match(&event.user) => {
+                Err(format!("user '{}' does not match '{}'", event.user, regex))
+            }
+            Rule::AllowedActions(actions) if !actions.contains(&event.action) => {
+                Err(format!("action '{}' is not allowed", event.action))
+            }
+            Rule::MaxDuration(max) if event.duration_ms > *max => {
+                Err(format!("duration_ms {} exceeds {}", event.duration_ms, max))
+            }
+            Rule::NoFutureTs(tolerance) => match event.timestamp() {
+                Some(ts) if ts > now + *tolerance => Err(format!("ts {} is in the future", event.ts)),
+                _ => Ok(()),
+            },
+            Rule::MaxTsAge(max) => match event.timestamp() {
+                Some(ts) if ts < now - *max => Err(format!("ts {} is older than {}", event.ts, format_age(*max))),
+                _ => Ok(()),
+            },
+            Rule::MaxFieldLength(max) => {
+                for (field, value) in [("user", &event.user), ("action", &event.action)] {
+                    let length = value.chars().count();
+                    if length > *max {
+                        return Err(format!("{} is {} characters long, max {}", field, length, max));
+                    }
+                }
+                Ok(())
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Formats a rule age limit for messages, e.g. 30d
+fn format_age(age: TimeDelta) -> String {
+    age.to_std()
+        .map(|age| humantime::format_duration(age).to_string())
+        .unwrap_or_else(|_| age.to_string())
+}
+
+/// Violation count of one rule
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RuleCount {
+    pub enabled: bool,
+    pub violations: usize,
+}
+
+/// Validation rule results over all events
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ValidationSummary {
+    /// Events rejected by at least one rule, counted as bad lines
+    pub rejected_events: usize,
+    /// Every configured rule by name
+    pub rules: BTreeMap<String, RuleCount>,
+}
+
+/// Configured validation rules, checked on top of Event::is_valid
+#[derive(Debug, Clone)]
+pub struct RuleSet {
+    rules: Vec<(Rule, bool)>,
+    /// Reference time of the timestamp rules, fixed when the rules are loaded
+    now: DateTime<Utc>,
+}
+
+impl RuleSet {
+    /// Parses a rules file, timestamp rules are relative to now
+    pub fn from_toml_str(content: &str, now: DateTime<Utc>) -> Result<Self, String> {
+        let file: RulesFile = toml::from_str(content).map_err(|e| e.to_string())?;
+
+        let mut rules = Vec::new();
+        if let Some(config) = file.user_pattern {
+            let regex = Regex::new(&config.pattern).map_err(|e| format!("user_pattern: {}", e))?;
+            rules.push((Rule::UserPattern(regex), config.enabled));
+        }
+        if let Some(config) = file.allowed_actions {
+            rules.push((Rule::AllowedActions(config.actions.into_iter().collect()), config.enabled));
+        }
+        if let Some(config) = file.max_duration {
+            let max = duration("max_duration", &config.max)?;
+            let max_ms = u64::try_from(max.num_milliseconds()).unwrap_or(u64::MAX);
+            rules.push((Rule::MaxDuration(max_ms), config.enabled));
+        }
+        if let Some(config) = file.no_future_ts {
+            let tolerance = match config.tolerance {
+                Some(ref tolerance) => duration("no_future_ts", tolerance)?,
+                None => TimeDelta::zero(),
+            };
+            rules.push((Rule::NoFutureTs(tolerance), config.enabled));
+        }
+        if let Some(config) = file.max_ts_age {
+            rules.push((Rule::MaxTsAge(duration("max_ts_age", &config.max)?), config.enabled));
+        }
+        if let Some(config) = file.max_field_length {
+            rules.push((Rule::MaxFieldLength(config.max), config.enabled));
+        }
+
+        Ok(RuleSet { rules, now })
+    }
+
+    /// Loads a rules file relative to the current time
+    pub fn from_toml_file(path: &str) -> Result<Self, String> {
+        let content = fs::read_to_string(path).map_err(|e| format!("cannot read {}: {}", path, e))?;
+        Self::from_toml_str(&content, Utc::now()).map_err(|e| format!("invalid rules file {}: {}", path, e))
+    }
+
+    /// Turns a rule off. Returns an error if the name is not a rule
+    pub fn disable(&mut self, name: &str) -> Result<(), String> {
+        if !RULE_NAMES.contains(&name) {
+            return Err(format!("unknown rule '{}', expected one of {}", name, RULE_NAMES.join(", ")));
+        }
+        for (rule, enabled) in &mut self.rules {
+            if rule.name() == name {
+                *enabled = false;
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the name and reason of every enabled rule the event breaks
+    pub fn violations(&self, event: &Event) -> Vec<(&'static str, String)> {
+        self.rules
+            .iter()
+            .filter(|(_, enabled)| *enabled)
+            .filter_map(|(rule, _)| rule.check(event, self.now).err().map(|reason| (rule.name(), reason)))
+            .collect()
+    }
+
+    /// Summary with every configured rule and no violations yet
+    pub fn empty_summary(&self) -> ValidationSummary {
+        ValidationSummary {
+            rejected_events: 0,
+            rules: self
+                .rules
+                .iter()
+                .map(|(rule, enabled)| {
+                    let count = RuleCount {
+                        enabled: *enabled,
+                        violations: 0,
+                    };
+                    (rule.name().to_string(), count)
+                })
+                .collect(),
+        }
+    }
+}
+
+impl ValidationSummary {
+    /// Records the violations of one rejected event
+    /// Returns true if a counter overflowed
+    pub fn record(&mut self, violations: &[(&'static str, String)]) -> bool {
+        let mut overflowed = stats::add_count(&mut self.rejected_events, 1);
+        for (name, _) in violations {
+            overflowed |= stats::add_count(&mut self.rules.entry(name.to_string()).or_default().violations, 1);
+        }
+        overflowed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::event::Level;
+
+    const RULES: &str = r#"
+[user_pattern]
+pattern = "^[a-z]+$"
+
+[allowed_actions]
+actions = ["login", "logout"]
+
+[max_duration]
+max = "24h"
+
+[no_future_ts]
+tolerance = "5m"
+
+[max_ts_age]
+max = "30d"
+
+[max_field_length]
+max = 8
+enabled = false
+"#;
+
+    fn now() -> DateTime<Utc> {
+        "2026-01-19T12:00:00Z".parse().unwrap()
+    }
+
+    fn event(ts: &str, user: &str, action: &str, duration_ms: u64) -> Event {
+        Event { ts: ts.to_string(), duration_ms, ..Event::test(user, action, Level::Info) }
+    }
+
+    fn names(rules: &RuleSet, event: &Event) -> Vec<&'static str> {
+        rules.violations(event).into_iter().map(|(name, _)| name).collect()
+    }
+
+    #[test]
+    fn test_valid_event() {
+        let rules = RuleSet::from_toml_str(RULES, now()).unwrap();
+
+        assert!(rules.violations(&event("2026-01-19T11:00:00Z", "alice", "login", 120)).is_empty());
+    }
+
+    #[test]
+    fn test_each_rule() {
+        let rules = RuleSet::from_toml_str(RULES, now()).unwrap();
+
+        assert_eq!(names(&rules, &event("2026-01-19T11:00:00Z", "Alice1", "login", 1)), ["user_pattern"]);
+        assert_eq!(names(&rules, &event("2026-01-19T11:00:00Z", "alice", "run", 1)), ["allowed_actions"]);
+        assert_eq!(
+            names(&rules, &event("2026-01-19T11:00:00Z", "alice", "login", 86_400_001)),
+            ["max_duration"]
+        );
+        assert_eq!(names(&rules, &event("2026-01-19T12:06:00Z", "alice", "login", 1)), ["no_future_ts"]);
+        assert_eq!(names(&rules, &event("2025-12-01T00:00:00Z", "alice", "login", 1)), ["max_ts_age"]);
+        // Within the future tolerance, unparsed timestamps are skipped
+        assert!(names(&rules, &event("2026-01-19T12:04:00Z", "alice", "login", 1)).is_empty());
+        assert!(names(&rules, &event("yesterday", "alice", "login", 1)).is_empty());
+    }
+
+    #[test]
+    fn test_toggle_rules() {
+        let mut rules = RuleSet::from_toml_str(RULES, now()).unwrap();
+        let long_user = event("2026-01-19T11:00:00Z", "abcdefghij", "login", 1);
+        // max_field_length is disabled in the file
+        assert!(rules.violations(&long_user).is_empty());
+
+        rules.disable("allowed_actions").unwrap();
+        assert!(rules.violations(&event("2026-01-19T11:00:00Z", "alice", "run", 1)).is_empty());
+        assert!(rules.disable("nope").is_err());
+
+        let summary = rules.empty_summary();
+        assert!(!summary.rules["allowed_actions"].enabled);
+        assert!(!summary.rules["max_field_length"].enabled);
+        assert!(summary.rules["user_pattern"].enabled);
+    }
+
+    #[test]
+    fn test_summary_counts() {
+        let rules = RuleSet::from_toml_str(RULES, now()).unwrap();
+        let mut summary = rules.empty_summary();
+
+        summary.record(&rules.violations(&event("2026-01-19T11:00:00Z", "Bob", "run", 1)));
+
+        assert_eq!(summary.rejected_events, 1);
+        assert_eq!(summary.rules["user_pattern"].violations, 1);
+        assert_eq!(summary.rules["allowed_actions"].violations, 1);
+        assert_eq!(summary.rules["max_duration"].violations, 0);
+    }
+
+    #[test]
+    fn test_invalid_rules() {
+        assert!(RuleSet::from_toml_str("[user_pattern]\npattern = \"[\"", now()).is_err());
+        assert!(RuleSet::from_toml_str("[max_duration]\nmax = \"soon\"", now()).is_err());
+        assert!(RuleSet::from_toml_str("[nope]\nmax = 1", now()).is_err());
+    }
+}
@@ -0,0 +1,431 @@
+use crate::event::Event;
+use crate::stats;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+
+/// z value of a two-sided 95% confidence interval
+const Z_95: f64 = 1.96;
+
+/// How valid events are sampled
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SampleMode {
+    /// Each event is kept independently with this probability
+    Bernoulli(f64),
+    /// A uniform sample of at most this many events
+    Reservoir(usize),
+    /// All events of a user are kept or dropped together, users are kept with this probability
+    ByUser(f64),
+}
+
+impl SampleMode {
+    /// Name reported in the sampling section
+    pub fn name(&self) -> &'static str {
+        match self {
+            SampleMode::Bernoulli(_) => "bernoulli",
+            SampleMode::Reservoir(_) => "reservoir",
+            SampleMode::ByUser(_) => "by_user",
+        }
+    }
+}
+
+/// What to do with an event offered to the sampler
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Decision {
+    /// Drop the event
+    Skip,
+    /// Add the event to the sample
+    Keep,
+    /// Replace the sampled event at this index (reservoir only)
+    Replace(usize),
+}
+
+/// SplitMix64, a small seeded generator so samples are reproducible
+#[derive(Debug, Clone)]
+pub struct SplitMix64 {
+    state: u64,
+}
+
+impl SplitMix64 {
+    pub fn new(seed: u64) -> Self {
+        SplitMix64 { state: seed }
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        mix(self.state)
+    }
+
+    /// Uniform in [0, 1)
+    fn next_f64(&mut self) -> f64 {
+        unit(self.next_u64())
+    }
+
+    /// Uniform in [0, n)
+    fn below(&mut self, n: usize) -> usize {
+        ((u128::from(self.next_u64()) * n as u128) >> 64) as usize
+    }
+}
+
+/// SplitMix64 finalizer
+fn mix(mut z: u64) -> u64 {
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+/// Maps a u64 to [0, 1)
+fn unit(value: u64) -> f64 {
+    (value >> 11) as f64 / (1u64 << 53) as f64
+}
+
+/// Stable hash of a user name, the same across runs and platforms
+fn user_hash(user: &str, seed: u64) -> u64 {
+    // FNV-1a, then mixed so similar names spread evenly
+    let hash = user.bytes().fold(0xCBF2_9CE4_8422_2325u64, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0000_0100_0000_01B3)
+    });
+    mix(hash ^ seed)
+}
+
+/// Uniform sample of at most size items from a stream (Algorithm R)
+#[derive(Debug, Clone)]
+pub struct Reservoir<T> {
+    size: usize,
+    seen: usize,
+    items: Vec<T>,
+}
+
+impl<T> Reservoir<T> {
+    pub fn new(size: usize) -> Self {
+        Reservoir {
+            size,
+            seen: 0,
+            items: Vec::new(),
+        }
+    }
+
+    /// Offers the next item of the stream. make is only called if the item is kept
+    pub fn offer_with(&mut self, rng: &mut SplitMix64, make: impl FnOnce() -> T) {
+        self.seen = self.seen.saturating_add(1);
+        if self.items.len() < self.size {
+            self.items.push(make());
+        } else {
+            let slot = rng.below(self.seen);
+            if slot < self.size {
+                self.items[slot] = make();
+            }
+        }
+    }
+
+    /// Items offered so far
+    pub fn seen(&self) -> usize {
+        self.seen
+    }
+
+    /// The sampled items
+    pub fn items(&self) -> &[T] {
+        &self.items
+    }
+}
+
+/// Decides which valid events enter the aggregation
+pub struct Sampler {
+    mode: SampleMode,
+    seed: u64,
+    rng: SplitMix64,
+    /// Events offered so far
+    seen: usize,
+    /// Events currently in the sample
+    kept: usize,
+    /// True once seen or kept saturated
+    overflowed: bool,
+}
+
+impl Sampler {
+    pub fn new(mode: SampleMode, seed: u64) -> Self {
+        Sampler {
+            mode,
+            seed,
+            rng: SplitMix64::new(seed),
+            seen: 0,
+            kept: 0,
+            overflowed: false,
+        }
+    }
+
+    /// Offers the next valid event to the sampler
+    pub fn decide(&mut self, event: &Event) -> Decision {
+        self.overflowed |= stats::add_count(&mut self.seen, 1);
+        let decision = match self.mode {
+            SampleMode::Bernoulli(rate) => {
+                if self.rng.next_f64() < rate {
+                    Decision::Keep
+                } else {
+                    Decision::Skip
+                }
+            }
+            SampleMode::ByUser(rate) => {
+                if unit(user_hash(&event.user, self.seed)) < rate {
+                    Decision::Keep
+                } else {
+                    Decision::Skip
+                }
+            }
+            // Algorithm R: the i-th event replaces a random slot with probability n / i
+            SampleMode::Reservoir(size) if self.kept < size => Decision::Keep,
+            SampleMode::Reservoir(size) => match self.rng.below(self.seen) {
+                slot if slot < size => Decision::Replace(slot),
+                _ => Decision::Skip,
+            },
+        };
+        if decision == Decision::Keep {
+            self.overflowed |= stats::add_count(&mut self.kept, 1);
+        }
+        decision
+    }
+
+    /// True if the seen or kept counter saturated at usize::MAX
+    pub fn overflowed(&self) -> bool {
+        self.overflowed
+    }
+
+    /// Summarizes the sample and estimates the full counts from the sampled events
+    pub fn summary(&self, sampled: &[Event]) -> SamplingSummary {
+        let mut levels: Vec<&str> = sampled.iter().map(|event| event.level.name()).collect();
+        levels.sort_unstable();
+        levels.dedup();
+
+        let (rate, estimated_events, estimated_by_level) = match self.mode {
+            SampleMode::Bernoulli(rate) | SampleMode::ByUser(rate) => {
+                let by_user = matches!(self.mode, SampleMode::ByUser(_));
+                let estimate = |events: &mut dyn Iterator<Item = &Event>| {
+                    Estimate::poisson(&group_sizes(events, by_user), rate)
+                };
+                let by_level = levels
+                    .iter()
+                    .map(|&level| {
+                        let mut events = sampled.iter().filter(|event| event.level.name() == level);
+                        (level.to_string(), estimate(&mut events))
+                    })
+                    .collect();
+                (rate, estimate(&mut sampled.iter()), by_level)
+            }
+            SampleMode::Reservoir(_) => {
+                let estimate = |matching| Estimate::simple_random(matching, sampled.len(), self.seen);
+                let by_level = levels
+                    .iter()
+                    .map(|&level| {
+                        let matching = sampled.iter().filter(|event| event.level.name() == level).count();
+                        (level.to_string(), estimate(matching))
+                    })
+                    .collect();
+                let rate = if self.seen == 0 { 1.0 } else { sampled.len() as f64 / self.seen as f64 };
+                (rate, estimate(sampled.len()), by_level)
+            }
+        };
+
+        SamplingSummary {
+            mode: self.mode.name().to_string(),
+            rate,
+            seen_events: self.seen,
+            sampled_events: sampled.len(),
+            estimated_events,
+            estimated_by_level,
+        }
+    }
+}
+
+/// Number of sampled events per sampling unit: per user, or one per event
+fn group_sizes(events: &mut dyn Iterator<Item = &Event>, by_user: bool) -> Vec<usize> {
+    if !by_user {
+        return events.map(|_| 1).collect();
+    }
+    let mut sizes: HashMap<&str, usize> = HashMap::new();
+    for event in events {
+        *sizes.entry(event.user.as_str()).or_insert(0) += 1;
+    }
+    sizes.into_values().collect()
+}
+
+/// Estimated full count with a 95% confidence interval (normal approximation)
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Estimate {
+    pub value: f64,
+    pub ci95_low: f64,
+    pub ci95_high: f64,
+}
+
+impl Estimate {
+    /// Horvitz-Thompson estimate when each unit was kept independently with probability rate
+    /// sizes holds the sampled count of each kept unit
+    fn poisson(sizes: &[usize], rate: f64) -> Self {
+        let observed: f64 = sizes.iter().map(|&size| size as f64).sum();
+        let squares: f64 = sizes.iter().map(|&size| (size as f64).powi(2)).sum();
+        let value = observed / rate;
+        let margin = Z_95 * ((1.0 - rate) / (rate * rate) * squares).sqrt();
+
+        Estimate::rounded(value, (value - margin).max(observed), value + margin)
+    }
+
+    /// Estimate from a uniform sample of sampled events out of population,
+    /// matching of which were counted
+    fn simple_random(matching: usize, sampled: usize, population: usize) -> Self {
+        if sampled == 0 {
+            return Estimate::default();
+        }
+        let (k, n, total) = (matching as f64, sampled as f64, population as f64);
+        let share = k / n;
+        let value = total * share;
+        let variance = if sampled > 1 {
+            total * total * (1.0 - n / total) * share * (1.0 - share) / (n - 1.0)
+        } else {
+            0.0
+        };
+        let margin = Z_95 * variance.sqrt();
+
+        Estimate::rounded(value, (value - margin).max(k), (value + margin).min(total - (n - k)))
+    }
+
+    /// Rounds to one decimal
+    fn rounded(value: f64, low: f64, high: f64) -> Self {
+        let round = |x: f64| (x * 10.0).round() / 10.0;
+        Estimate {
+            value: round(value),
+            ci95_low: round(low),
+            ci95_high: round(high),
+        }
+    }
+}
+
+/// Marks a sampled summary: its counts cover the sample only, the estimates scale them up
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SamplingSummary {
+    /// bernoulli, reservoir or by_user
+    pub mode: String,
+    /// Sampling probability, for the reservoir the share of events sampled
+    pub rate: f64,
+    /// Valid events before sampling
+    pub seen_events: usize,
+    /// Valid events in the sample, the events of the summary
+    pub sampled_events: usize,
+    /// Estimated valid events in the full input
+    pub estimated_events: Estimate,
+    /// Estimated events per level in the full input
+    pub estimated_by_level: BTreeMap<String, Estimate>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::event::Level;
+
+    fn run(sampler: &mut Sampler, events: &[Event]) -> Vec<Event> {
+        let mut sampled = Vec::new();
+        for event in events {
+            match sampler.decide(event) {
+                Decision::Skip => {}
+                Decision::Keep => sampled.push(event.clone()),
+                Decision::Replace(slot) => sampled[slot] = event.clone(),
+            }
+        }
+        sampled
+    }
+
+    fn events(n: usize) -> Vec<Event> {
+        (0..n)
+            .map(|i| {
+                let level = if i % 10 == 0 { Level::Error } else { Level::Info };
+                Event::test(&format!("user{}", i % 50), "login", level)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_bernoulli_estimate_covers_truth() {
+        let events = events(20_000);
+        let mut sampler = Sampler::new(SampleMode::Bernoulli(0.1), 7);
+
+        let sampled = run(&mut sampler, &events);
+        let summary = sampler.summary(&sampled);
+
+        assert_eq!(summary.seen_events, 20_000);
+        assert!(summary.estimated_events.ci95_low <= 20_000.0 && summary.estimated_events.ci95_high >= 20_000.0);
+        let errors = &summary.estimated_by_level["ERROR"];
+        assert!(errors.ci95_low <= 2_000.0 && errors.ci95_high >= 2_000.0);
+    }
+
+    #[test]
+    fn test_bernoulli_is_reproducible() {
+        let events = events(1_000);
+
+        let first = run(&mut Sampler::new(SampleMode::Bernoulli(0.5), 1), &events);
+        let second = run(&mut Sampler::new(SampleMode::Bernoulli(0.5), 1), &events);
+
+        assert_eq!(first, second);
+    }
+
+    #[test]
+    fn test_reservoir() {
+        let events = events(10_000);
+        let mut sampler = Sampler::new(SampleMode::Reservoir(500), 3);
+
+        let sampled = run(&mut sampler, &events);
+        let summary = sampler.summary(&sampled);
+
+        assert_eq!(sampled.len(), 500);
+        assert_eq!(summary.rate, 0.05);
+        assert_eq!(summary.estimated_events.value, 10_000.0);
+        assert_eq!(summary.estimated_events.ci95_low, 10_000.0);
+        let errors = &summary.estimated_by_level["ERROR"];
+        assert!(errors.ci95_low <= 1_000.0 && errors.ci95_high >= 1_000.0);
+    }
+
+    #[test]
+    fn test_small_reservoir_is_exact() {
+        let events = events(20);
+        let mut sampler = Sampler::new(SampleMode::Reservoir(100), 0);
+
+        let sampled = run(&mut sampler, &events);
+        let summary = sampler.summary(&sampled);
+
+        assert_eq!(sampled, events);
+        assert_eq!(summary.estimated_by_level["ERROR"], Estimate::rounded(2.0, 2.0, 2.0));
+    }
+
+    #[test]
+    fn test_generic_reservoir() {
+        let mut rng = SplitMix64::new(0);
+        let mut reservoir = Reservoir::new(10);
+        let mut made = 0;
+        for i in 0..1_000 {
+            reservoir.offer_with(&mut rng, || {
+                made += 1;
+                i
+            });
+        }
+
+        let mut items = reservoir.items().to_vec();
+        items.sort_unstable();
+        items.dedup();
+        assert_eq!(items.len(), 10);
+        assert_eq!(reservoir.seen(), 1_000);
+        // Items past the first ten are only made when they replace one
+        assert!(made < 100);
+    }
+
+    #[test]
+    fn test_by_user_keeps_whole_users() {
+        let events = events(5_000);
+        let mut sampler = Sampler::new(SampleMode::ByUser(0.2), 0);
+
+        let sampled = run(&mut sampler, &events);
+        let mut users: Vec<&str> = sampled.iter().map(|event| event.user.as_str()).collect();
+        users.sort_unstable();
+        users.dedup();
+
+        // Every event of a sampled user is kept
+        assert_eq!(sampled.len(), users.len() * 100);
+        let again = run(&mut Sampler::new(SampleMode::ByUser(0.2), 0), &events);
+        assert_eq!(sampled, again);
+    }
+}
@@ -0,0 +1,175 @@
+use crate::event::Event;
+use crate::stats;
+use jsonschema::Validator;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fs;
+
+/// JSON Schema every valid event must satisfy
+#[derive(Debug, Clone)]
+pub struct EventSchema {
+    validator: Validator,
+}
+
+/// A single failed schema rule
+#[derive(Debug, Clone, PartialEq)]
+pub struct Violation {
+    /// Schema location of the failed keyword, e.g. `/properties/duration_ms/maximum`
+    pub rule: String,
+    /// Human readable reason including the offending field
+    pub reason: String,
+}
+
+/// Schema violation counts over all events
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SchemaViolations {
+    /// Events rejected by the schema, counted as bad lines
+    pub invalid_events: usize,
+    /// Violations per schema rule. An event can violate several rules
+    pub by_rule: BTreeMap<String, usize>,
+}
+
+impl SchemaViolations {
+    /// Records the violations of one rejected event
+    /// Returns true if a counter overflowed
+    pub fn record(&mut self, violations: &[Violation]) -> bool {
+        let mut overflowed = stats::add_count(&mut self.invalid_events, 1);
+        for violation in violations {
+            overflowed |= stats::add_count(self.by_rule.entry(violation.rule.clone()).or_insert(0), 1);
+        }
+        overflowed
+    }
+}
+
+impl EventSchema {
+    /// Compiles a schema. Returns the reason if it is not a valid JSON Schema
+    pub fn from_value(schema: &Value) -> Result<Self, String> {
+        let validator = jsonschema::validator_for(schema).map_err(|e| format!("invalid schema: {}", e))?;
+        Ok(EventSchema { validator })
+    }
+
+    /// Loads and compiles a JSON Schema file
+    pub fn from_file(path: &str) -> Result<Self, String> {
+        let content = fs::read_to_string(path).map_err(|e| format!("cannot read {}: {}", path, e))?;
+        let schema: Value =
+            serde_json::from_str(&content).map_err(|e| format!("invalid schema {}: {}", path, e))?;
+        Self::from_value(&schema).map_err(|e| format!("{} in {}", e, path))
+    }
+
+    /// Validates an event, as JSON including its extra fields
+    /// Returns every violated rule, empty if the event is valid
+    pub fn violations(&self, event: &Event) -> Vec<Violation> {
+        match serde_json::to_value(event) {
+            Ok(instance) => self.value_violations(&instance),
+            Err(e) => vec![Violation {
+                rule: String::new(),
+                reason: format!("cannot convert event to JSON: {}", e),
+            }],
+        }
+    }
+
+    /// Validates a JSON value as read from the input, before any mapping or coercion
+    /// Returns every violated rule, empty if the value is valid
+    pub fn value_violations(&self, instance: &Value) -> Vec<Violation> {
+        self.validator
+            .iter_errors(instance)
+            .map(|error| {
+                let field = error.instance_path().as_str();
+                Violation {
+                    rule: error.schema_path().as_str().to_string(),
+                    reason: if field.is_empty() {
+                        error.to_string()
+                    } else {
+                        format!("{}: {}", field, error)
+                    },
+                }
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::severity::LevelScale;
+    use serde_json::json;
+
+    fn schema() -> EventSchema {
+        EventSchema::from_value(&json!({
+            "type": "object",
+            "required": ["region"],
+            "properties": {
+                "user": {"type": "string", "pattern": "^[a-z]+$"},
+                "action": {"enum": ["login", "logout"]},
+                "duration_ms": {"maximum": 1000},
+                "region": {"type": "string", "minLength": 2}
+            }
+        }))
+        .unwrap()
+    }
+
+    fn event(line: &str) -> Event {
+        Event::from_json_line(line, &LevelScale::default()).unwrap()
+    }
+
+    #[test]
+    fn test_valid_event() {
+        let event =
+            event(r#"{"ts":"2026-01-19T12:00:01Z","level":"INFO","user":"alice","action":"login","duration_ms":10,"region":"eu"}"#);
+
+        assert!(schema().violations(&event).is_empty());
+    }
+
+    #[test]
+    fn test_violations() {
+        let event =
+            event(r#"{"ts":"2026-01-19T12:00:01Z","level":"INFO","user":"Alice1","action":"run","duration_ms":5000}"#);
+
+        let violations = schema().violations(&event);
+        let rules: Vec<&str> = violations.iter().map(|v| v.rule.as_str()).collect();
+
+        assert_eq!(violations.len(), 4);
+        assert!(rules.contains(&"/required"));
+        assert!(rules.contains(&"/properties/user/pattern"));
+        assert!(rules.contains(&"/properties/action/enum"));
+        assert!(rules.contains(&"/properties/duration_ms/maximum"));
+        assert!(violations.iter().any(|v| v.reason.starts_with("/duration_ms: ")));
+    }
+
+    #[test]
+    fn test_raw_value_violations() {
+        let raw = json!({"ts": "t", "level": "info", "user": "alice", "action": "login", "duration_ms": "12", "region": "eu"});
+        let typed = EventSchema::from_value(&json!({"properties": {"duration_ms": {"type": "integer"}}})).unwrap();
+
+        let violations = typed.value_violations(&raw);
+
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].rule, "/properties/duration_ms/type");
+        assert!(schema().value_violations(&raw).is_empty());
+    }
+
+    #[test]
+    fn test_violation_counts() {
+        let mut counts = SchemaViolations::default();
+        let violation = Violation {
+            rule: "/required".to_string(),
+            reason: "\"region\" is a required property".to_string(),
+        };
+
+        counts.record(std::slice::from_ref(&violation));
+        counts.record(&[violation]);
+
+        assert_eq!(counts.invalid_events, 2);
+        assert_eq!(counts.by_rule["/required"], 2);
+
+        counts.invalid_events = usize::MAX;
+        assert!(counts.record(&[]));
+        assert_eq!(counts.invalid_events, usize::MAX);
+    }
+
+    #[test]
+    fn test_invalid_schema() {
+        assert!(EventSchema::from_value(&json!({"type": "nope"})).is_err());
+    }
+}
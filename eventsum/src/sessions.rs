@@ -0,0 +1,168 @@
+use crate::event::Event;
+use crate::stats::{self, Distribution, NameCount};
+use crate::timeline::Timelines;
+use chrono::{DateTime, TimeDelta, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::time::Duration;
+
+/// Number of entries in the first/last action rankings
+const TOP_ACTIONS: usize = 3;
+
+/// Summary of per-user sessions split by inactivity gap
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionSummary {
+    /// Inactivity gap that starts a new session, in milliseconds
+    pub session_gap_ms: u64,
+    /// Number of distinct users with at least one session
+    pub users: usize,
+    /// Total number of sessions across all users
+    pub sessions: usize,
+    /// Valid events skipped because their ts could not be parsed
+    pub unparsed_timestamps: usize,
+    /// Time between first and last event of a session
+    pub session_length_ms: Distribution,
+    /// Number of events per session
+    pub actions_per_session: Distribution,
+    /// Most common actions that start a session
+    pub top_first_actions: Vec<NameCount>,
+    /// Most common actions that end a session
+    pub top_last_actions: Vec<NameCount>,
+}
+
+/// A single reconstructed session
+struct Session<'a> {
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    first_action: &'a str,
+    last_action: &'a str,
+    actions: u64,
+}
+
+impl SessionSummary {
+    /// Groups each user's events into sessions and summarizes them
+    pub fn from_events(events: &[Event], gap: Duration) -> Self {
+        let max_gap = TimeDelta::from_std(gap).unwrap_or(TimeDelta::MAX);
+        let timelines = Timelines::from_events(events);
+
+        let mut sessions: Vec<Session> = Vec::new();
+        for timeline in timelines.by_user.values() {
+            let mut current: Option<Session> = None;
+            for &(ts, action) in timeline.iter() {
+                match current {
+                    Some(ref mut session) if ts - session.end <= max_gap => {
+                        session.end = ts;
+                        session.last_action = action;
+                        session.actions += 1;
+                    }
+                    _ => {
+                        sessions.extend(current.take());
+                        current = Some(Session {
+                            start: ts,
+                            end: ts,
+                            first_action: action,
+                            last_action: action,
+                            actions: 1,
+                        });
+                    }
+                }
+            }
+            sessions.extend(current);
+        }
+
+        let mut first_actions: HashMap<String, usize> = HashMap::new();
+        let mut last_actions: HashMap<String, usize> = HashMap::new();
+        for session in &sessions {
+            *first_actions.entry(session.first_action.to_string()).or_insert(0) += 1;
+            *last_actions.entry(session.last_action.to_string()).or_insert(0) += 1;
+        }
+
+        SessionSummary {
+            session_gap_ms: gap.as_millis().try_into().unwrap_or(u64::MAX),
+            users: timelines.by_user.len(),
+            sessions: sessions.len(),
+            unparsed_timestamps: timelines.unparsed_timestamps,
+            session_length_ms: Distribution::from_values(
+                sessions
+                    .iter()
+                    .map(|s| (s.end - s.start).num_milliseconds() as u64)
+                    .collect(),
+            ),
+            actions_per_session: Distribution::from_values(
+                sessions.iter().map(|s| s.actions).collect(),
+            ),
+            top_first_actions: stats::top_counts(&first_actions, TOP_ACTIONS),
+            top_last_actions: stats::top_counts(&last_actions, TOP_ACTIONS),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::event::Level;
+
+    fn event(ts: &str, user: &str, action: &str) -> Event {
+        Event { ts: ts.to_string(), duration_ms: 10, ..Event::test(user, action, Level::Info) }
+    }
+
+    #[test]
+    fn test_sessions_split_by_gap() {
+        let events = vec![
+            event("2026-01-19T12:00:00Z", "alice", "login"),
+            event("2026-01-19T12:10:00Z", "alice", "run_script"),
+            event("2026-01-19T12:20:00Z", "alice", "logout"),
+            // 2h later: new session
+            event("2026-01-19T14:20:00Z", "alice", "login"),
+        ];
+
+        let summary = SessionSummary::from_events(&events, Duration::from_secs(30 * 60));
+
+        assert_eq!(summary.users, 1);
+        assert_eq!(summary.sessions, 2);
+        assert_eq!(summary.session_length_ms.max, 20 * 60 * 1000);
+        assert_eq!(summary.session_length_ms.min, 0);
+        assert_eq!(summary.actions_per_session.max, 3);
+        assert_eq!(summary.top_first_actions[0].name, "login");
+        assert_eq!(summary.top_first_actions[0].count, 2);
+    }
+
+    #[test]
+    fn test_sessions_out_of_order_input() {
+        let events = vec![
+            event("2026-01-19T12:20:00Z", "bob", "logout"),
+            event("2026-01-19T12:00:00Z", "bob", "login"),
+        ];
+
+        let summary = SessionSummary::from_events(&events, Duration::from_secs(30 * 60));
+
+        assert_eq!(summary.sessions, 1);
+        assert_eq!(summary.top_first_actions[0].name, "login");
+        assert_eq!(summary.top_last_actions[0].name, "logout");
+    }
+
+    #[test]
+    fn test_sessions_per_user() {
+        let events = vec![
+            event("2026-01-19T12:00:00Z", "alice", "login"),
+            event("2026-01-19T12:00:01Z", "bob", "login"),
+            event("2026-01-19T12:00:02Z", "alice", "logout"),
+        ];
+
+        let summary = SessionSummary::from_events(&events, Duration::from_secs(60));
+
+        assert_eq!(summary.users, 2);
+        assert_eq!(summary.sessions, 2);
+    }
+
+    #[test]
+    fn test_sessions_unparsed_timestamp() {
+        let events = vec![event("not-a-time", "alice", "login")];
+
+        let summary = SessionSummary::from_events(&events, Duration::from_secs(60));
+
+        assert_eq!(summary.unparsed_timestamps, 1);
+        assert_eq!(summary.sessions, 0);
+        assert_eq!(summary.session_length_ms.count, 0);
+    }
+}
@@ -0,0 +1,100 @@
+use crate::event::Level;
+use std::collections::HashMap;
+use std::fs;
+
+/// Highest severity number, same range as OpenTelemetry severity numbers (1-24)
+pub const MAX_SEVERITY: u8 = 24;
+
+/// User-defined levels and their severity, on the same 1-24 scale as the builtin levels
+///
+/// Loaded from a TOML file with one `NAME = severity` entry per level, e.g. `VERBOSE = 3`.
+/// Names are case-insensitive and must not redefine a builtin level
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct LevelScale {
+    levels: HashMap<String, u8>,
+}
+
+impl LevelScale {
+    /// Parses the TOML content of a scale file
+    pub fn from_toml_str(content: &str) -> Result<Self, String> {
+        let table: HashMap<String, i64> = toml::from_str(content).map_err(|e| e.to_string())?;
+
+        let mut levels = HashMap::new();
+        for (name, severity) in table {
+            let upper = name.trim().to_ascii_uppercase();
+            if upper.is_empty() {
+                return Err("empty level name".to_string());
+            }
+            if Level::BUILTIN.iter().any(|level| level.name() == upper) {
+                return Err(format!("level {} redefines a builtin level", name));
+            }
+            let severity = u8::try_from(severity)
+                .ok()
+                .filter(|s| (1..=MAX_SEVERITY).contains(s))
+                .ok_or_else(|| format!("severity of {} must be between 1 and {}", name, MAX_SEVERITY))?;
+            if levels.insert(upper, severity).is_some() {
+                return Err(format!("level {} is defined twice", name));
+            }
+        }
+
+        Ok(LevelScale { levels })
+    }
+
+    /// Loads a scale file
+    pub fn from_toml_file(path: &str) -> Result<Self, String> {
+        let content = fs::read_to_string(path).map_err(|e| format!("cannot read {}: {}", path, e))?;
+        Self::from_toml_str(&content).map_err(|e| format!("invalid level scale {}: {}", path, e))
+    }
+
+    /// Returns the severity of an upper case level name
+    pub fn severity(&self, name: &str) -> Option<u8> {
+        self.levels.get(name).copied()
+    }
+
+    /// Parses a level name case-insensitively, builtin levels first, then the levels of the scale
+    pub fn parse_level(&self, s: &str) -> Result<Level, String> {
+        if let Ok(level) = s.parse::<Level>() {
+            return Ok(level);
+        }
+        let upper = s.to_ascii_uppercase();
+        match self.severity(&upper) {
+            Some(severity) => Ok(Level::Custom { name: upper, severity }),
+            None => Err(format!("unknown level '{}'", s)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scale_from_toml() {
+        let scale = LevelScale::from_toml_str("verbose = 3\nSEVERE = 18\n").unwrap();
+
+        assert_eq!(scale.severity("VERBOSE"), Some(3));
+        assert_eq!(scale.severity("SEVERE"), Some(18));
+        assert_eq!(scale.severity("verbose"), None);
+        assert_eq!(scale.severity("AUDIT"), None);
+    }
+
+    #[test]
+    fn test_invalid_scales() {
+        assert!(LevelScale::from_toml_str("info = 3").is_err());
+        assert!(LevelScale::from_toml_str("VERBOSE = 0").is_err());
+        assert!(LevelScale::from_toml_str("VERBOSE = 25").is_err());
+        assert!(LevelScale::from_toml_str("VERBOSE = \"low\"").is_err());
+        assert!(LevelScale::from_toml_str("verbose = 3\nVERBOSE = 4").is_err());
+    }
+
+    #[test]
+    fn test_parse_level_with_scale() {
+        let scale = LevelScale::from_toml_str("VERBOSE = 3").unwrap();
+        let other = LevelScale::from_toml_str("VERBOSE = 19").unwrap();
+
+        assert_eq!(scale.parse_level("info").unwrap(), Level::Info);
+        assert_eq!(scale.parse_level("verbose").unwrap().severity(), 3);
+        assert!(other.parse_level("VERBOSE").unwrap().is_error());
+        assert!(LevelScale::default().parse_level("VERBOSE").is_err());
+    }
+}
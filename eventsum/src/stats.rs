@@ -0,0 +1,176 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Returns the value at the given percentile (0-100) using the nearest-rank method
+/// rank = ceil(pct * n / 100) (1-based), then value = sorted[rank - 1]
+/// The rank is computed exactly in integer arithmetic, so it is correct for any n.
+/// Returns the default (0) if the slice is empty. The slice must be sorted ascending
+pub fn nearest_rank<T: Copy + Default>(sorted: &[T], pct: u32) -> T {
+    if sorted.is_empty() {
+        return T::default();
+    }
+
+    let n = sorted.len();
+    let rank = (u128::from(pct.min(100)) * n as u128).div_ceil(100) as usize;
+    sorted[rank.clamp(1, n) - 1]
+}
+
+/// Adds n to a counter, saturating at usize::MAX
+/// Returns true if the counter overflowed
+pub fn add_count(counter: &mut usize, n: usize) -> bool {
+    match counter.checked_add(n) {
+        Some(sum) => {
+            *counter = sum;
+            false
+        }
+        None => {
+            *counter = usize::MAX;
+            true
+        }
+    }
+}
+
+/// Summary statistics over a list of values
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Distribution {
+    pub count: usize,
+    pub min: u64,
+    pub p50: u64,
+    pub p95: u64,
+    pub max: u64,
+    pub mean: f64,
+}
+
+impl Distribution {
+    /// Computes the distribution of the given values. All zero if empty
+    pub fn from_values(mut values: Vec<u64>) -> Self {
+        if values.is_empty() {
+            return Distribution::default();
+        }
+
+        values.sort_unstable();
+        // At most usize::MAX values below 2^64 each, so the sum fits in a u128
+        let sum: u128 = values.iter().map(|&v| v as u128).sum();
+
+        Distribution {
+            count: values.len(),
+            min: values[0],
+            p50: nearest_rank(&values, 50),
+            p95: nearest_rank(&values, 95),
+            max: values[values.len() - 1],
+            mean: sum as f64 / values.len() as f64,
+        }
+    }
+}
+
+/// Name with an occurrence count, used for ranked lists
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NameCount {
+    pub name: String,
+    pub count: usize,
+}
+
+/// Returns the top n entries by count (descending), then by name (ascending)
+pub fn top_counts(counts: &HashMap<String, usize>, n: usize) -> Vec<NameCount> {
+    let mut ranked: Vec<NameCount> = counts
+        .iter()
+        .map(|(name, &count)| NameCount {
+            name: name.clone(),
+            count,
+        })
+        .collect();
+
+    ranked.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.name.cmp(&b.name)));
+    ranked.truncate(n);
+    ranked
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    #[test]
+    fn test_nearest_rank() {
+        let values = vec![10, 20, 120, 400, 900];
+
+        // n=5, p50 rank=ceil(2.5)=3, p95 rank=ceil(4.75)=5
+        assert_eq!(nearest_rank(&values, 50), 120);
+        assert_eq!(nearest_rank(&values, 95), 900);
+        assert_eq!(nearest_rank::<u64>(&[], 95), 0);
+    }
+
+    #[test]
+    fn test_distribution() {
+        let dist = Distribution::from_values(vec![30, 10, 20]);
+
+        assert_eq!(dist.count, 3);
+        assert_eq!(dist.min, 10);
+        assert_eq!(dist.p50, 20);
+        assert_eq!(dist.max, 30);
+        assert_eq!(dist.mean, 20.0);
+    }
+
+    #[test]
+    fn test_top_counts_tie_breaker() {
+        let mut counts = HashMap::new();
+        counts.insert("b".to_string(), 2);
+        counts.insert("a".to_string(), 2);
+        counts.insert("c".to_string(), 5);
+
+        let top = top_counts(&counts, 2);
+
+        assert_eq!(top.len(), 2);
+        assert_eq!(top[0].name, "c");
+        assert_eq!(top[1].name, "a");
+    }
+
+    #[test]
+    fn test_add_count_saturates() {
+        let mut count = usize::MAX - 1;
+
+        assert!(!add_count(&mut count, 1));
+        assert_eq!(count, usize::MAX);
+        assert!(add_count(&mut count, 1));
+        assert_eq!(count, usize::MAX);
+    }
+
+    #[test]
+    fn test_extreme_durations() {
+        let dist = Distribution::from_values(vec![u64::MAX; 3]);
+
+        assert_eq!(dist.p95, u64::MAX);
+        assert_eq!(dist.mean, u64::MAX as f64);
+    }
+
+    proptest! {
+        /// The rank is the smallest one covering at least pct percent of the values
+        #[test]
+        fn prop_nearest_rank_is_exact(n in 1usize..5000, pct in 0u32..=100) {
+            let values: Vec<usize> = (1..=n).collect();
+            let rank = nearest_rank(&values, pct);
+
+            prop_assert!(rank >= 1 && rank <= n);
+            prop_assert!(rank * 100 >= pct as usize * n || rank == 1);
+            prop_assert!((rank - 1) * 100 < pct as usize * n || rank == 1);
+        }
+
+        #[test]
+        fn prop_distribution_within_bounds(values in prop::collection::vec(prop_oneof![Just(u64::MAX), Just(0u64), any::<u64>()], 1..200)) {
+            let dist = Distribution::from_values(values.clone());
+
+            prop_assert_eq!(dist.count, values.len());
+            prop_assert!(dist.min <= dist.p50 && dist.p50 <= dist.p95 && dist.p95 <= dist.max);
+            prop_assert!(dist.mean >= dist.min as f64 && dist.mean <= dist.max as f64);
+        }
+
+        #[test]
+        fn prop_add_count_never_wraps(start in any::<usize>(), n in any::<usize>()) {
+            let mut count = start;
+            let overflowed = add_count(&mut count, n);
+
+            prop_assert_eq!(overflowed, start.checked_add(n).is_none());
+            prop_assert_eq!(count, start.saturating_add(n));
+        }
+    }
+}
@@ -0,0 +1,320 @@
+use crate::event::{Event, Level};
+use crate::logfmt;
+use chrono::{DateTime, Datelike, NaiveDateTime, Utc};
+use serde_json::Value;
+use std::collections::HashMap;
+
+/// Maps a syslog severity (PRI % 8) to the level of the same name
+fn severity_level(severity: u8) -> Level {
+    match severity {
+        0 => Level::Emergency,
+        1 => Level::Alert,
+        2 => Level::Critical,
+        3 => Level::Error,
+        4 => Level::Warn,
+        5 => Level::Notice,
+        6 => Level::Info,
+        _ => Level::Debug,
+    }
+}
+
+/// Splits `<PRI>rest` into the severity and the rest of the line
+fn parse_pri(line: &str) -> Result<(u8, &str), String> {
+    let rest = line.strip_prefix('<').ok_or("missing <PRI>")?;
+    let end = rest.find('>').ok_or("unterminated <PRI>")?;
+    let pri: u8 = rest[..end]
+        .parse()
+        .ok()
+        .filter(|pri| *pri <= 191)
+        .ok_or_else(|| format!("invalid PRI '{}'", &rest[..end]))?;
+    Ok((pri % 8, &rest[end + 1..]))
+}
+
+/// Splits off the next space separated token
+fn next_token(s: &str) -> Option<(&str, &str)> {
+    let s = s.trim_start_matches(' ');
+    if s.is_empty() {
+        return None;
+    }
+    match s.find(' ') {
+        Some(end) => Some((&s[..end], &s[end + 1..])),
+        None => Some((s, "")),
+    }
+}
+
+/// Parses RFC 5424 STRUCTURED-DATA (`-` or `[id k="v" ...][id2 ...]`)
+/// Params of all elements are merged, the first occurrence of a name wins.
+/// Returns the params and the remaining MSG part
+fn parse_structured_data(s: &str) -> Result<(HashMap<String, String>, &str), String> {
+    let mut params = HashMap::new();
+    if let Some(rest) = s.strip_prefix('-') {
+        return Ok((params, rest));
+    }
+
+    let mut chars = s.char_indices().peekable();
+    let mut end = 0;
+    while let Some(&(_, '[')) = chars.peek() {
+        chars.next();
+        // SD-ID up to the first space or ]
+        while let Some(&(_, c)) = chars.peek() {
+            if c == ' ' || c == ']' {
+                break;
+            }
+            chars.next();
+        }
+
+        loop {
+            match chars.next() {
+                Some((i, ']')) => {
+                    end = i + 1;
+                    break;
+                }
+                Some((_, ' ')) => {
+                    let mut name = String::new();
+                    for (_, c) in chars.by_ref() {
+                        if c == '=' {
+                            break;
+                        }
+                        name.push(c);
+                    }
+                    if chars.next().map(|(_, c)| c) != Some('"') {
+                        return Err(format!("unquoted SD param '{}'", name));
+                    }
+                    let mut value = String::new();
+                    let mut closed = false;
+                    while let Some((_, c)) = chars.next() {
+                        match c {
+                            '"' => {
+                                closed = true;
+                                break;
+                            }
+                            '\\' => {
+                                if let Some((_, escaped)) = chars.next() {
+                                    value.push(escaped);
+                                }
+                            }
+                            other => value.push(other),
+                        }
+                    }
+                    if !closed {
+                        return Err(format!("unterminated SD param '{}'", name));
+                    }
+                    params.entry(name).or_insert(value);
+                }
+                _ => return Err("malformed STRUCTURED-DATA".to_string()),
+            }
+        }
+    }
+
+    if end == 0 {
+        return Err("missing STRUCTURED-DATA".to_string());
+    }
+    Ok((params, &s[end..]))
+}
+
+/// Extracts key=value pairs from a free text MSG, ignoring text that is not logfmt
+fn msg_pairs(msg: &str) -> HashMap<String, String> {
+    msg.split_whitespace()
+        .filter(|token| token.contains('='))
+        .filter_map(|token| logfmt::parse_pairs(token).ok())
+        .flatten()
+        .collect()
+}
+
+/// Builds the event from the header fields and extracted params
+fn build_event(
+    severity: u8,
+    ts: String,
+    app_name: Option<&str>,
+    msg_id: Option<&str>,
+    params: &HashMap<String, String>,
+    duration_key: &str,
+) -> Result<Event, String> {
+    let param = |key: &str| params.get(key).map(String::as_str).filter(|v| !v.is_empty());
+
+    let user = param("user").or(app_name).ok_or("missing user and APP-NAME")?;
+    let action = param("action")
+        .or(msg_id)
+        .or(app_name)
+        .ok_or("missing action, MSGID and APP-NAME")?;
+    let duration_ms = match param(duration_key) {
+        Some(value) => value
+            .parse()
+            .map_err(|_| format!("invalid {} '{}'", duration_key, value))?,
+        None => 0,
+    };
+
+    Ok(Event {
+        ts,
+        level: severity_level(severity),
+        user: user.to_string(),
+        action: action.to_string(),
+        duration_ms,
+        extra: params
+            .iter()
+            .filter(|(key, _)| !["user", "action", duration_key].contains(&key.as_str()))
+            .map(|(key, value)| (key.clone(), Value::String(value.clone())))
+            .collect(),
+    })
+}
+
+/// Parses an RFC 5424 line after the PRI
+fn parse_rfc5424(severity: u8, rest: &str, duration_key: &str) -> Result<Event, String> {
+    let nil = |token: &str| -> Option<String> { (token != "-").then(|| token.to_string()) };
+
+    let (_version, rest) = next_token(rest).ok_or("missing VERSION")?;
+    let (timestamp, rest) = next_token(rest).ok_or("missing TIMESTAMP")?;
+    let (_hostname, rest) = next_token(rest).ok_or("missing HOSTNAME")?;
+    let (app_name, rest) = next_token(rest).ok_or("missing APP-NAME")?;
+    let (_proc_id, rest) = next_token(rest).ok_or("missing PROCID")?;
+    let (msg_id, rest) = next_token(rest).ok_or("missing MSGID")?;
+
+    let ts = match nil(timestamp) {
+        Some(ts) => DateTime::parse_from_rfc3339(&ts)
+            .map_err(|e| format!("invalid TIMESTAMP '{}': {}", ts, e))?
+            .with_timezone(&Utc)
+            .to_rfc3339_opts(chrono::SecondsFormat::AutoSi, true),
+        None => String::new(),
+    };
+
+    let (mut params, msg) = parse_structured_data(rest)?;
+    let msg = msg.trim_start_matches(' ').trim_start_matches('\u{feff}');
+    for (key, value) in msg_pairs(msg) {
+        params.entry(key).or_insert(value);
+    }
+
+    let app_name = nil(app_name);
+    let msg_id = nil(msg_id);
+    build_event(severity, ts, app_name.as_deref(), msg_id.as_deref(), &params, duration_key)
+}
+
+/// Adds the year that puts an RFC 3164 timestamp (`Jan  9 12:00:01`) closest to the reference
+fn with_closest_year(timestamp: &str, reference: DateTime<Utc>) -> Result<DateTime<Utc>, String> {
+    let year = reference.year();
+    [year - 1, year, year + 1]
+        .into_iter()
+        .filter_map(|year| {
+            NaiveDateTime::parse_from_str(&format!("{} {}", year, timestamp), "%Y %b %e %H:%M:%S").ok()
+        })
+        .map(|ts| ts.and_utc())
+        .min_by_key(|ts| (*ts - reference).abs())
+        .ok_or_else(|| format!("invalid TIMESTAMP '{}'", timestamp))
+}
+
+/// Parses an RFC 3164 line after the PRI
+/// The timestamp has no year and zone: UTC and the year closest to the reference time are assumed
+fn parse_rfc3164(severity: u8, rest: &str, duration_key: &str, reference: DateTime<Utc>) -> Result<Event, String> {
+    let timestamp = rest.get(..15).ok_or("missing TIMESTAMP")?;
+    let ts = with_closest_year(timestamp, reference)?.to_rfc3339_opts(chrono::SecondsFormat::AutoSi, true);
+
+    let (_hostname, rest) = next_token(&rest[15..]).ok_or("missing HOSTNAME")?;
+    let tag_end = rest.find([':', '[', ' ']).unwrap_or(rest.len());
+    let tag = &rest[..tag_end];
+    let msg = rest[tag_end..]
+        .split_once(':')
+        .map(|(_, msg)| msg)
+        .unwrap_or(&rest[tag_end..]);
+
+    let params = msg_pairs(msg);
+    let tag = (!tag.is_empty()).then_some(tag);
+    build_event(severity, ts, tag, None, &params, duration_key)
+}
+
+/// Parses an RFC 5424 or RFC 3164 syslog line into an Event
+///
+/// - level: severity of PRI
+/// - user: `user` SD param or MSG key, else APP-NAME (TAG for RFC 3164)
+/// - action: `action` SD param or MSG key, else MSGID, else APP-NAME
+/// - duration_ms: SD param or MSG key named duration_key, 0 if absent
+/// - other SD params and MSG keys become string extra fields
+///
+/// RFC 3164 timestamps get the year closest to reference, e.g. the previous event's time
+pub fn parse_line(line: &str, duration_key: &str, reference: DateTime<Utc>) -> Result<Event, String> {
+    let (severity, rest) = parse_pri(line)?;
+
+    // RFC 5424 has a version number right after the PRI
+    let is_rfc5424 = rest
+        .split_once(' ')
+        .is_some_and(|(version, _)| !version.is_empty() && version.chars().all(|c| c.is_ascii_digit()));
+    if is_rfc5424 {
+        parse_rfc5424(severity, rest, duration_key)
+    } else {
+        parse_rfc3164(severity, rest, duration_key, reference)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn reference() -> DateTime<Utc> {
+        "2026-01-19T12:00:00Z".parse().unwrap()
+    }
+
+    #[test]
+    fn test_rfc5424_structured_data() {
+        let line = r#"<165>1 2026-01-19T13:00:01.003+01:00 host1 appliance 1234 ID47 [meta@32473 user="alice" action="run \"x\"" duration_ms="120"][other x="y"] done"#;
+
+        let event = parse_line(line, "duration_ms", reference()).unwrap();
+
+        // 165 % 8 = 5 (notice)
+        assert_eq!(event.level, Level::Notice);
+        assert_eq!(event.ts, "2026-01-19T12:00:01.003Z");
+        assert_eq!(event.user, "alice");
+        assert_eq!(event.action, r#"run "x""#);
+        assert_eq!(event.duration_ms, 120);
+    }
+
+    #[test]
+    fn test_rfc5424_fallbacks() {
+        let line = "<11>1 2026-01-19T12:00:01Z host1 backup - SYNC - latency=42 finished";
+
+        let event = parse_line(line, "latency", reference()).unwrap();
+
+        // 11 % 8 = 3 (err)
+        assert_eq!(event.level, Level::Error);
+        assert_eq!(event.user, "backup");
+        assert_eq!(event.action, "SYNC");
+        assert_eq!(event.duration_ms, 42);
+    }
+
+    #[test]
+    fn test_rfc3164() {
+        let line = "<12>Jan  9 12:00:01 host1 sshd[42]: user=bob action=login took it";
+
+        let event = parse_line(line, "duration_ms", reference()).unwrap();
+
+        // 12 % 8 = 4 (warning)
+        assert_eq!(event.level, Level::Warn);
+        assert_eq!(event.ts, "2026-01-09T12:00:01Z");
+        assert_eq!(event.user, "bob");
+        assert_eq!(event.action, "login");
+        assert_eq!(event.duration_ms, 0);
+    }
+
+    #[test]
+    fn test_rfc3164_year_rollover() {
+        let december = "<14>Dec 31 23:59:59 host1 app: action=backup";
+        let january = "<14>Jan  1 00:00:01 host1 app: action=backup";
+
+        // Read in January, a December line is from the previous year
+        assert_eq!(parse_line(december, "duration_ms", reference()).unwrap().ts, "2025-12-31T23:59:59Z");
+
+        // Following a December event, a January line is from the next year
+        let previous: DateTime<Utc> = "2025-12-31T23:59:59Z".parse().unwrap();
+        assert_eq!(parse_line(january, "duration_ms", previous).unwrap().ts, "2026-01-01T00:00:01Z");
+
+        // Feb 29 only exists in leap years
+        let leap = "<14>Feb 29 10:00:00 host1 app: action=x";
+        let reference = "2029-01-01T00:00:00Z".parse().unwrap();
+        assert_eq!(parse_line(leap, "duration_ms", reference).unwrap().ts, "2028-02-29T10:00:00Z");
+    }
+
+    #[test]
+    fn test_invalid_lines() {
+        assert!(parse_line("no pri", "duration_ms", reference()).is_err());
+        assert!(parse_line("<999>1 - - - - - -", "duration_ms", reference()).is_err());
+        assert!(parse_line("<14>1 2026-01-19T12:00:01Z h a p m [broken", "duration_ms", reference()).is_err());
+        assert!(parse_line("<14>garbage", "duration_ms", reference()).is_err());
+    }
+}
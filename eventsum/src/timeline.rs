@@ -0,0 +1,46 @@
+use crate::event::Event;
+use chrono::{DateTime, Utc};
+use log::warn;
+use std::collections::HashMap;
+
+/// Time-ordered (timestamp, action) pairs of a single user
+pub type Timeline<'a> = Vec<(DateTime<Utc>, &'a str)>;
+
+/// Per-user timelines built from valid events
+pub struct Timelines<'a> {
+    /// Timeline per user, sorted by timestamp
+    pub by_user: HashMap<&'a str, Timeline<'a>>,
+    /// Events skipped because their ts could not be parsed
+    pub unparsed_timestamps: usize,
+}
+
+impl<'a> Timelines<'a> {
+    /// Groups events by user and orders each group by parsed timestamp
+    /// Events with equal timestamps keep their input order
+    pub fn from_events(events: &'a [Event]) -> Self {
+        let mut by_user: HashMap<&str, Timeline> = HashMap::new();
+        let mut unparsed_timestamps = 0;
+
+        for event in events {
+            match event.timestamp() {
+                Some(ts) => by_user
+                    .entry(event.user.as_str())
+                    .or_default()
+                    .push((ts, event.action.as_str())),
+                None => {
+                    warn!("Skipping event with unparsable ts: {}", event.ts);
+                    unparsed_timestamps += 1;
+                }
+            }
+        }
+
+        for timeline in by_user.values_mut() {
+            timeline.sort_by_key(|(ts, _)| *ts);
+        }
+
+        Timelines {
+            by_user,
+            unparsed_timestamps,
+        }
+    }
+}